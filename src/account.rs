@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use sui_sdk::types::{
+    base_types::ObjectID, programmable_transaction_builder::ProgrammableTransactionBuilder,
+};
+use sui_types::collection_types::VecSet;
+
+use crate::client::TradingClient;
+use crate::config::price_from_units;
+use crate::queries::return_value;
+use crate::transactions::move_call;
+use crate::types::{Account, Balances, MAX_TIMESTAMP, Side};
+
+/// Upper bound on dev-inspect calls in flight when fanning out across pools.
+pub const MAX_CONCURRENT_QUERIES: usize = 8;
+
+/// Upper bound on Move calls batched into a single dev-inspect PTB.
+pub const MAX_CALLS_PER_INSPECT: usize = 256;
+
+/// Read-only manager queries. Each takes a configured manager key (falling back to the
+/// default manager) and has a `_by_id` variant that accepts any balance manager's
+/// object ID, for inspecting managers the client does not own.
+impl TradingClient {
+    /// Get the (base, quote, deep) balances a pool holds for a manager, in human units.
+    /// As with `pool::locked_balance`, this is what the manager's open orders lock plus
+    /// its settled balances on the pool; subtract `Account::settled_balances` for the
+    /// open orders alone.
+    pub async fn get_locked_balance(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<(f64, f64, f64)> {
+        self.get_locked_balance_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn get_locked_balance_by_id(
+        &self,
+        pool_key: &str,
+        manager_id: ObjectID,
+    ) -> Result<(f64, f64, f64)> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+        let locked = self
+            .get_locked_balance_units_by_id(pool_key, manager_id)
+            .await?;
+
+        Ok((
+            base.from_units(locked.base),
+            quote.from_units(locked.quote),
+            deep.from_units(locked.deep),
+        ))
+    }
+
+    /// Like `get_locked_balance`, in on-chain units.
+    pub async fn get_locked_balance_units(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<Balances> {
+        self.get_locked_balance_units_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn get_locked_balance_units_by_id(
+        &self,
+        pool_key: &str,
+        manager_id: ObjectID,
+    ) -> Result<Balances> {
+        let pool_id = self.config.pool(pool_key)?.id()?;
+        if let Some(locked) = self.ctx.locked_balance(pool_id, manager_id) {
+            return Ok(locked);
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, Some(manager_id)).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "locked_balance",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        Ok(Balances {
+            base: return_value(&results, 0, 0)?,
+            quote: return_value(&results, 0, 1)?,
+            deep: return_value(&results, 0, 2)?,
+        })
+    }
+
+    /// Whether the manager has an account on the pool, i.e. has ever traded on it.
+    pub async fn account_exists(&self, pool_key: &str, manager_key: Option<&str>) -> Result<bool> {
+        self.account_exists_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn account_exists_by_id(&self, pool_key: &str, manager_id: ObjectID) -> Result<bool> {
+        if let Some(account) = self
+            .ctx
+            .account(self.config.pool(pool_key)?.id()?, manager_id)
+        {
+            return Ok(account.is_some());
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, Some(manager_id)).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "account_exists",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        return_value(&results, 0, 0)
+    }
+
+    /// Get the IDs of the manager's open orders on a pool.
+    pub async fn get_open_orders(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<Vec<u128>> {
+        self.get_open_orders_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn get_open_orders_by_id(
+        &self,
+        pool_key: &str,
+        manager_id: ObjectID,
+    ) -> Result<Vec<u128>> {
+        // account_open_orders aborts for managers that never traded on the pool.
+        if !self.account_exists_by_id(pool_key, manager_id).await? {
+            return Ok(vec![]);
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, Some(manager_id)).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "account_open_orders",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        let order_ids: VecSet<u128> = return_value(&results, 0, 0)?;
+        Ok(order_ids.contents)
+    }
+
+    /// Get each open order's remaining life against the build context's clock: `None`
+    /// for orders that never expire, zero for orders already past their expiration.
+    pub async fn get_orders_with_expiry(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<Vec<(u128, Option<Duration>)>> {
+        let order_ids = self.get_open_orders(pool_key, manager_key).await?;
+        if order_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let now_ms = self.ctx.now_ms();
+        Ok(self
+            .get_orders(pool_key, &order_ids)
+            .await?
+            .into_iter()
+            .map(|order| {
+                let time_left = (order.expire_timestamp != MAX_TIMESTAMP)
+                    .then(|| Duration::from_millis(order.expire_timestamp.saturating_sub(now_ms)));
+                (order.order_id, time_left)
+            })
+            .collect())
+    }
+
+    /// Get the manager's open orders on every pool in `pool_keys`, keyed by pool.
+    /// Pools without open orders are left out of the map.
+    pub async fn get_all_open_orders(
+        &self,
+        manager_key: Option<&str>,
+        pool_keys: &[&str],
+    ) -> Result<HashMap<String, Vec<u128>>> {
+        let manager_id = self.manager_id(manager_key)?;
+        let results: Vec<(String, Result<Vec<u128>>)> = stream::iter(pool_keys)
+            .map(|pool_key| async move {
+                (
+                    pool_key.to_string(),
+                    self.get_open_orders_by_id(pool_key, manager_id).await,
+                )
+            })
+            .buffer_unordered(MAX_CONCURRENT_QUERIES)
+            .collect()
+            .await;
+
+        let mut open_orders = HashMap::new();
+        for (pool_key, result) in results {
+            let order_ids = result?;
+            if !order_ids.is_empty() {
+                open_orders.insert(pool_key, order_ids);
+            }
+        }
+        Ok(open_orders)
+    }
+
+    /// Get the manager's account on a pool, or `None` if it never traded there.
+    pub async fn get_account(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<Option<Account>> {
+        self.get_account_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn get_account_by_id(
+        &self,
+        pool_key: &str,
+        manager_id: ObjectID,
+    ) -> Result<Option<Account>> {
+        if let Some(account) = self
+            .ctx
+            .account(self.config.pool(pool_key)?.id()?, manager_id)
+        {
+            return Ok(account);
+        }
+
+        if !self.account_exists_by_id(pool_key, manager_id).await? {
+            return Ok(None);
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, Some(manager_id)).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "account",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        Ok(Some(return_value(&results, 0, 0)?))
+    }
+
+    /// Get the manager's free balance of a coin, in human units.
+    pub async fn get_manager_balance(
+        &self,
+        manager_key: Option<&str>,
+        coin_key: &str,
+    ) -> Result<f64> {
+        self.get_manager_balance_by_id(self.manager_id(manager_key)?, coin_key)
+            .await
+    }
+
+    pub async fn get_manager_balance_by_id(
+        &self,
+        manager_id: ObjectID,
+        coin_key: &str,
+    ) -> Result<f64> {
+        let coin = self.config.coin(coin_key)?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let manager_arg = ptb.obj(self.ctx.shared_object(manager_id, false).await?)?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "balance_manager",
+            "balance",
+            vec![coin.type_tag()?],
+            vec![manager_arg],
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        let balance: u64 = return_value(&results, 0, 0)?;
+        Ok(coin.from_units(balance))
+    }
+
+    /// Get the free balance of a coin for many managers, batching one `balance` call per
+    /// manager into as few dev-inspect runs as `MAX_CALLS_PER_INSPECT` allows.
+    pub async fn get_balances_for_managers(
+        &self,
+        manager_keys: &[&str],
+        coin_key: &str,
+    ) -> Result<HashMap<String, f64>> {
+        let coin = self.config.coin(coin_key)?;
+        let mut balances = HashMap::with_capacity(manager_keys.len());
+        for chunk in manager_keys.chunks(MAX_CALLS_PER_INSPECT) {
+            let mut ptb = ProgrammableTransactionBuilder::new();
+            for manager_key in chunk {
+                let manager_id = self.balance_manager_id(manager_key)?;
+                let manager_arg = ptb.obj(self.ctx.shared_object(manager_id, false).await?)?;
+                move_call(
+                    &mut ptb,
+                    self.config.package_id()?,
+                    "balance_manager",
+                    "balance",
+                    vec![coin.type_tag()?],
+                    vec![manager_arg],
+                )?;
+            }
+
+            let results = self.dev_inspect(ptb).await?;
+            for (command, manager_key) in chunk.iter().enumerate() {
+                let balance: u64 = return_value(&results, command, 0)?;
+                balances.insert(manager_key.to_string(), coin.from_units(balance));
+            }
+        }
+        Ok(balances)
+    }
+
+    /// The manager's position change if every open order on the pool filled, in human
+    /// units: `(net_base, quote_committed)`. Bids add their remaining base and spend
+    /// quote, asks remove base and bring quote in, so both values are net of the two
+    /// sides and either may be negative.
+    pub async fn get_potential_fill_position(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<(f64, f64)> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let order_ids = self.get_open_orders(pool_key, manager_key).await?;
+        let orders = self.get_orders(pool_key, &order_ids).await?;
+        let (mut net_base, mut quote_committed) = (0.0, 0.0);
+        for order in orders {
+            let quantity = base.from_units(order.remaining_quantity());
+            let notional = quantity * price_from_units(order.price(), base, quote);
+            match order.side() {
+                Side::Bid => {
+                    net_base += quantity;
+                    quote_committed += notional;
+                }
+                Side::Ask => {
+                    net_base -= quantity;
+                    quote_committed -= notional;
+                }
+            }
+        }
+        Ok((net_base, quote_committed))
+    }
+
+    /// Get the manager's (active, inactive) DEEP stake on a pool, in human units.
+    pub async fn get_stake(&self, pool_key: &str, manager_key: Option<&str>) -> Result<(f64, f64)> {
+        self.get_stake_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn get_stake_by_id(
+        &self,
+        pool_key: &str,
+        manager_id: ObjectID,
+    ) -> Result<(f64, f64)> {
+        let deep = self.config.coin("DEEP")?;
+        Ok(match self.get_account_by_id(pool_key, manager_id).await? {
+            Some(account) => (
+                deep.from_units(account.active_stake),
+                deep.from_units(account.inactive_stake),
+            ),
+            None => (0.0, 0.0),
+        })
+    }
+
+    /// Get the DEEP rebates the manager has accrued on a pool but not yet claimed, in
+    /// human units. Rebates from the last finished epoch are only credited once the
+    /// account is next touched, so they may not show up here until then.
+    pub async fn get_claimable_rebates(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<f64> {
+        self.get_claimable_rebates_by_id(pool_key, self.manager_id(manager_key)?)
+            .await
+    }
+
+    pub async fn get_claimable_rebates_by_id(
+        &self,
+        pool_key: &str,
+        manager_id: ObjectID,
+    ) -> Result<f64> {
+        let deep = self.config.coin("DEEP")?;
+        Ok(match self.get_account_by_id(pool_key, manager_id).await? {
+            Some(account) => deep.from_units(account.unclaimed_rebates.deep),
+            None => 0.0,
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::{Result, anyhow};
+use sui_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+
+use crate::client::TradingClient;
+use crate::config::{Coin, MAX_PRICE, MIN_PRICE, price_from_units, price_to_units};
+use crate::queries::return_value;
+use crate::transactions::move_call;
+use crate::types::{BookSummary, Level, OrderBook, OrderRole, Side};
+
+impl TradingClient {
+    /// Get the best bid and best ask in human units; `None` for an empty side.
+    pub async fn get_best_bid_ask(&self, pool_key: &str) -> Result<(Option<f64>, Option<f64>)> {
+        let book = self.get_order_book(pool_key, 1).await?;
+        Ok((
+            book.bids.first().map(|level| level.price),
+            book.asks.first().map(|level| level.price),
+        ))
+    }
+
+    /// Mid of the best bid and ask, or `None` if either side of the book is empty.
+    pub async fn get_mid_price(&self, pool_key: &str) -> Result<Option<f64>> {
+        Ok(match self.get_best_bid_ask(pool_key).await? {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => None,
+        })
+    }
+
+    /// Bid-ask spread relative to the mid, in basis points, or `None` if either side of
+    /// the book is empty.
+    pub async fn get_spread_bps(&self, pool_key: &str) -> Result<Option<f64>> {
+        Ok(match self.get_best_bid_ask(pool_key).await? {
+            (Some(bid), Some(ask)) => Some((ask - bid) / ((bid + ask) / 2.0) * 10_000.0),
+            _ => None,
+        })
+    }
+
+    /// Classify a limit order at `price` as maker or taker against the current book.
+    /// Prices are compared in on-chain units, so `price` matches a level it rounds to.
+    pub async fn classify_order(
+        &self,
+        pool_key: &str,
+        side: Side,
+        price: f64,
+    ) -> Result<OrderRole> {
+        let (best_bid, best_ask) = self.get_best_bid_ask(pool_key).await?;
+        let opposite = match side {
+            Side::Bid => best_ask,
+            Side::Ask => best_bid,
+        };
+        let Some(opposite) = opposite else {
+            return Ok(OrderRole::Maker);
+        };
+
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let price = price_to_units(price, base, quote);
+        let opposite = price_to_units(opposite, base, quote);
+
+        let role = if price == opposite {
+            OrderRole::Crossing
+        } else if (side == Side::Bid && price > opposite) || (side == Side::Ask && price < opposite)
+        {
+            OrderRole::Taker
+        } else {
+            OrderRole::Maker
+        };
+        Ok(role)
+    }
+
+    /// Get up to `depth` price levels on each side of the book, counted in ticks from
+    /// the mid price. An empty side comes back as an empty vec.
+    pub async fn get_order_book(&self, pool_key: &str, depth: usize) -> Result<OrderBook> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, mut args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        args.push(ptb.pure(depth as u64)?);
+        args.push(ptb.obj(self.ctx.clock())?);
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "get_level2_ticks_from_mid",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        let bid_prices: Vec<u64> = return_value(&results, 0, 0)?;
+        let bid_quantities: Vec<u64> = return_value(&results, 0, 1)?;
+        let ask_prices: Vec<u64> = return_value(&results, 0, 2)?;
+        let ask_quantities: Vec<u64> = return_value(&results, 0, 3)?;
+
+        Ok(OrderBook {
+            bids: levels(&bid_prices, &bid_quantities, base, quote, depth),
+            asks: levels(&ask_prices, &ask_quantities, base, quote, depth),
+        })
+    }
+
+    /// Count the levels and total quantity on each side of the whole book, reading both
+    /// sides over the full price range in one dev-inspect.
+    pub async fn get_book_summary(&self, pool_key: &str) -> Result<BookSummary> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, pool_args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        let price_low = ptb.pure(MIN_PRICE)?;
+        let price_high = ptb.pure(MAX_PRICE)?;
+        let clock = ptb.obj(self.ctx.clock())?;
+        for is_bid in [true, false] {
+            let mut args = pool_args.clone();
+            args.extend([price_low, price_high, ptb.pure(is_bid)?, clock]);
+            move_call(
+                &mut ptb,
+                self.config.package_id()?,
+                "pool",
+                "get_level2_range",
+                type_args.clone(),
+                args,
+            )?;
+        }
+
+        let results = self.dev_inspect(ptb).await?;
+        let bid_prices: Vec<u64> = return_value(&results, 0, 0)?;
+        let bid_quantities: Vec<u64> = return_value(&results, 0, 1)?;
+        let ask_prices: Vec<u64> = return_value(&results, 1, 0)?;
+        let ask_quantities: Vec<u64> = return_value(&results, 1, 1)?;
+
+        Ok(BookSummary {
+            bid_levels: bid_prices.len(),
+            ask_levels: ask_prices.len(),
+            total_bid_quantity: base.from_units(total_quantity(&bid_quantities)?),
+            total_ask_quantity: base.from_units(total_quantity(&ask_quantities)?),
+            best_bid: bid_prices
+                .iter()
+                .max()
+                .map(|price| price_from_units(*price, base, quote)),
+            best_ask: ask_prices
+                .iter()
+                .min()
+                .map(|price| price_from_units(*price, base, quote)),
+        })
+    }
+}
+
+/// Sum level quantities, failing rather than wrapping if the total exceeds `u64`.
+fn total_quantity(quantities: &[u64]) -> Result<u64> {
+    quantities
+        .iter()
+        .try_fold(0u64, |total, quantity| total.checked_add(*quantity))
+        .ok_or_else(|| anyhow!("Total book quantity overflows u64"))
+}
+
+/// Zip level2 price and quantity vectors into human-unit levels.
+pub fn levels(
+    prices: &[u64],
+    quantities: &[u64],
+    base: &Coin,
+    quote: &Coin,
+    depth: usize,
+) -> Vec<Level> {
+    prices
+        .iter()
+        .zip(quantities)
+        .take(depth)
+        .map(|(price, quantity)| Level {
+            price: price_from_units(*price, base, quote),
+            quantity: base.from_units(*quantity),
+            order_count: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_quantity_sums_levels() {
+        assert_eq!(total_quantity(&[]).unwrap(), 0);
+        assert_eq!(total_quantity(&[1, 2, u64::MAX - 3]).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn total_quantity_rejects_overflow() {
+        assert!(total_quantity(&[u64::MAX, 1]).is_err());
+    }
+}
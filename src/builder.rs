@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Result, anyhow};
+use sui_sdk::rpc_types::DevInspectResults;
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{Argument, ProgrammableTransaction, TransactionKind},
+};
+
+use crate::client::TradingClient;
+use crate::events::{manager_balance_changes, parse_event};
+use crate::types::{PlaceLimitOrderParams, PlaceMarketOrderParams, SwapParams};
+
+/// Outcome of `TransactionBuilder::simulate`.
+pub struct Simulation {
+    /// The finished PTB, ready for `execute`.
+    pub transaction: ProgrammableTransaction,
+    pub results: DevInspectResults,
+    /// Net change of each balance manager's funds per coin type, in on-chain units,
+    /// keyed by manager ID and coin type. Dev-inspect does not report the sender's
+    /// wallet balance changes, so coins withdrawn to the wallet only show up here as
+    /// the manager's loss.
+    pub balance_changes: BTreeMap<(ObjectID, String), i128>,
+}
+
+/// Chaining PTB builder that remembers every coin its calls produce.
+///
+/// Coins in Move cannot be dropped, so a PTB that leaves one unused aborts. Coins the
+/// builder hands out stay tracked until they are transferred, either with
+/// `transfer_remaining` or by passing them to `consume` after using them elsewhere.
+pub struct TransactionBuilder<'a> {
+    client: &'a TradingClient,
+    ptb: ProgrammableTransactionBuilder,
+    pending_coins: Vec<Argument>,
+    /// One TradeProof per manager, shared by every call the builder makes for it.
+    proofs: HashMap<String, Argument>,
+}
+
+impl<'a> TransactionBuilder<'a> {
+    pub fn new(client: &'a TradingClient) -> Self {
+        TransactionBuilder {
+            client,
+            ptb: ProgrammableTransactionBuilder::new(),
+            pending_coins: vec![],
+            proofs: HashMap::new(),
+        }
+    }
+
+    /// Direct access to the underlying PTB for calls the builder does not wrap.
+    pub fn ptb(&mut self) -> &mut ProgrammableTransactionBuilder {
+        &mut self.ptb
+    }
+
+    /// Coins produced so far that have not been transferred or consumed.
+    pub fn pending_coins(&self) -> &[Argument] {
+        &self.pending_coins
+    }
+
+    /// The manager's TradeProof for this PTB, generated on first use.
+    pub async fn proof(&mut self, manager_key: Option<&str>) -> Result<Argument> {
+        let manager_key = self.client.manager_key(manager_key)?;
+        if let Some(proof) = self.proofs.get(manager_key) {
+            return Ok(*proof);
+        }
+        let proof = self
+            .client
+            .generate_proof(&mut self.ptb, Some(manager_key))
+            .await?;
+        self.proofs.insert(manager_key.to_string(), proof);
+        Ok(proof)
+    }
+
+    /// Track a coin produced by a call made through `ptb()`.
+    pub fn track_coin(&mut self, coin: Argument) -> &mut Self {
+        self.pending_coins.push(coin);
+        self
+    }
+
+    /// Stop tracking a coin that has been passed on to another call.
+    pub fn consume(&mut self, coin: Argument) -> &mut Self {
+        self.pending_coins.retain(|pending| *pending != coin);
+        self
+    }
+
+    pub async fn place_limit_order(&mut self, params: &PlaceLimitOrderParams) -> Result<&mut Self> {
+        let proof = self.proof(params.balance_manager_key.as_deref()).await?;
+        self.client
+            .place_limit_order_with_proof(&mut self.ptb, params, proof)
+            .await?;
+        Ok(self)
+    }
+
+    /// Place a limit order funded by `coin`, which stops being tracked once deposited.
+    pub async fn place_limit_order_with_coin(
+        &mut self,
+        params: &PlaceLimitOrderParams,
+        coin_key: &str,
+        coin: Argument,
+    ) -> Result<&mut Self> {
+        self.client
+            .deposit_coin(
+                &mut self.ptb,
+                params.balance_manager_key.as_deref(),
+                coin_key,
+                coin,
+            )
+            .await?;
+        self.consume(coin);
+        let proof = self.proof(params.balance_manager_key.as_deref()).await?;
+        self.client
+            .place_funded_limit_order(&mut self.ptb, params, proof)
+            .await?;
+        Ok(self)
+    }
+
+    pub async fn place_market_order(
+        &mut self,
+        params: &PlaceMarketOrderParams,
+    ) -> Result<&mut Self> {
+        let proof = self.proof(params.balance_manager_key.as_deref()).await?;
+        self.client
+            .place_market_order_with_proof(&mut self.ptb, params, proof)
+            .await?;
+        Ok(self)
+    }
+
+    pub async fn cancel_order(
+        &mut self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        order_id: u128,
+    ) -> Result<&mut Self> {
+        let proof = self.proof(manager_key).await?;
+        self.client
+            .cancel_order_with_proof(&mut self.ptb, pool_key, manager_key, order_id, proof)
+            .await?;
+        Ok(self)
+    }
+
+    pub async fn cancel_all_orders(
+        &mut self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<&mut Self> {
+        let proof = self.proof(manager_key).await?;
+        self.client
+            .cancel_all_orders_with_proof(&mut self.ptb, pool_key, manager_key, proof)
+            .await?;
+        Ok(self)
+    }
+
+    pub async fn swap_exact_base_for_quote(&mut self, params: &SwapParams) -> Result<&mut Self> {
+        let (base, quote, deep) = self
+            .client
+            .swap_exact_base_for_quote(&mut self.ptb, params)
+            .await?;
+        self.pending_coins.extend([base, quote, deep]);
+        Ok(self)
+    }
+
+    pub async fn swap_exact_quote_for_base(&mut self, params: &SwapParams) -> Result<&mut Self> {
+        let (base, quote, deep) = self
+            .client
+            .swap_exact_quote_for_base(&mut self.ptb, params)
+            .await?;
+        self.pending_coins.extend([base, quote, deep]);
+        Ok(self)
+    }
+
+    pub async fn withdraw_settled_all(
+        &mut self,
+        manager_key: Option<&str>,
+        pool_keys: &[&str],
+    ) -> Result<&mut Self> {
+        let coins = self
+            .client
+            .withdraw_settled_all(&mut self.ptb, manager_key, pool_keys)
+            .await?;
+        self.pending_coins.extend(coins);
+        Ok(self)
+    }
+
+    pub async fn exit_pool(
+        &mut self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<&mut Self> {
+        let coins = self
+            .client
+            .exit_pool(&mut self.ptb, pool_key, manager_key)
+            .await?;
+        self.pending_coins.extend(coins);
+        Ok(self)
+    }
+
+    /// Transfer every still-tracked coin to `recipient`.
+    pub fn transfer_remaining(&mut self, recipient: SuiAddress) -> &mut Self {
+        if !self.pending_coins.is_empty() {
+            let coins = std::mem::take(&mut self.pending_coins);
+            self.ptb.transfer_args(recipient, coins);
+        }
+        self
+    }
+
+    /// Finish the PTB, refusing to if any coin would be left without a destination.
+    pub fn finish(self) -> Result<ProgrammableTransaction> {
+        if !self.pending_coins.is_empty() {
+            return Err(anyhow!(
+                "{} coin(s) were never transferred: {:?}; call transfer_remaining first",
+                self.pending_coins.len(),
+                self.pending_coins
+            ));
+        }
+        Ok(self.ptb.finish())
+    }
+
+    /// Finish the PTB and dev-inspect it as a unit, so a multi-order turn can be checked
+    /// for aborts and its effects and events inspected before signing. Fails with
+    /// `ClientError::DevInspectUnavailable` on nodes that do not serve dev-inspect.
+    pub async fn simulate(self) -> Result<Simulation> {
+        let client = self.client;
+        let pt = self.finish()?;
+        let results = client
+            .dev_inspect_kind(TransactionKind::ProgrammableTransaction(pt.clone()))
+            .await?;
+        let package_id = client.config.package_id()?;
+        let mut events = vec![];
+        for event in &results.events.data {
+            if let Some(parsed) = parse_event(event, package_id)? {
+                events.push(parsed);
+            }
+        }
+        Ok(Simulation {
+            balance_changes: manager_balance_changes(&events),
+            transaction: pt,
+            results,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use deepbookv3::types::BalanceManager;
+    use sui_sdk::types::{base_types::SequenceNumber, transaction::Command};
+
+    use super::*;
+    use crate::client::test_support::{move_call_names, offline_client};
+    use crate::context::BuildContext;
+    use crate::types::{PoolBookParams, Side};
+
+    const OTHER_MANAGER_ID: &str =
+        "0x73e7bc2f1007a4f1ffcc42af9305e4e7ce16274297e2e513b2503b9c85c287d4";
+
+    /// A client with a second manager, "OTHER", next to the default one.
+    fn client() -> TradingClient {
+        let mut client = offline_client(BuildContext::fixed());
+        let pool_id = client.config.pool("SUI_DBUSDC").unwrap().id().unwrap();
+        let other_id = ObjectID::from_hex_literal(OTHER_MANAGER_ID).unwrap();
+        client.ctx = client
+            .ctx
+            .clone()
+            .with_shared_version(other_id, SequenceNumber::from_u64(1))
+            .with_whitelisted(pool_id, false)
+            .with_book_params(
+                pool_id,
+                PoolBookParams {
+                    tick_size: 1_000,
+                    lot_size: 1_000_000,
+                    min_size: 10_000_000,
+                },
+            );
+        client.balance_managers.insert(
+            "OTHER".to_string(),
+            BalanceManager {
+                address: OTHER_MANAGER_ID,
+                trade_cap: None,
+                deposit_cap: None,
+                withdraw_cap: None,
+            },
+        );
+        client
+    }
+
+    fn bid(client_order_id: u64, manager_key: Option<&str>) -> PlaceLimitOrderParams {
+        PlaceLimitOrderParams {
+            pool_key: "SUI_DBUSDC".to_string(),
+            balance_manager_key: manager_key.map(str::to_string),
+            client_order_id,
+            price: 2.0,
+            quantity: 1.0,
+            side: Side::Bid,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn a_batch_of_orders_shares_one_proof_per_manager() {
+        let client = client();
+        let mut builder = TransactionBuilder::new(&client);
+        for client_order_id in 0..3 {
+            builder
+                .place_limit_order(&bid(client_order_id, None))
+                .await
+                .unwrap();
+        }
+        builder.cancel_order("SUI_DBUSDC", None, 1).await.unwrap();
+        builder
+            .place_limit_order(&bid(3, Some("OTHER")))
+            .await
+            .unwrap();
+        let pt = builder.finish().unwrap();
+
+        let proofs = move_call_names(&pt)
+            .iter()
+            .filter(|name| *name == "balance_manager::generate_proof_as_owner")
+            .count();
+        assert_eq!(proofs, 2);
+
+        // Every call for the default manager takes the proof generated first.
+        let proof_args: Vec<Argument> = pt
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                Command::MoveCall(call) if call.module.as_str() == "pool" => {
+                    Some(call.arguments[2])
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(proof_args.len(), 5);
+        assert!(
+            proof_args[..4]
+                .iter()
+                .all(|proof| *proof == Argument::Result(0))
+        );
+        assert_ne!(proof_args[4], Argument::Result(0));
+    }
+}
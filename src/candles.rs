@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use sui_sdk::{rpc_types::EventFilter, types::parse_sui_struct_tag};
+
+use crate::client::TradingClient;
+use crate::config::price_from_units;
+use crate::events::{DeepBookEvent, parse_event};
+
+/// Events fetched per `query_events` page; the RPC caps pages at 50.
+pub const EVENTS_PER_PAGE: usize = 50;
+/// Pages `get_ohlc` reads at most, bounding it to the newest
+/// `EVENTS_PER_PAGE * MAX_EVENT_PAGES` fills across all pools.
+pub const MAX_EVENT_PAGES: usize = 20;
+
+/// One OHLC bucket in human units; `volume` is in the base coin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    pub start_ms: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl TradingClient {
+    /// Build the last `count` candles of `interval` for a pool from `OrderFilled` events,
+    /// oldest first. The newest candle is the one still in progress.
+    ///
+    /// Empty intervals repeat the previous close with zero volume; intervals before the
+    /// first fill in the window are left out, so fewer than `count` candles may come
+    /// back. Fills are read newest first from the global event stream and only
+    /// `MAX_EVENT_PAGES` pages are fetched, so on busy markets the oldest candles can
+    /// be incomplete. Events are looked up under the configured package ID, which must
+    /// be the package that defines the event types.
+    pub async fn get_ohlc(
+        &self,
+        pool_key: &str,
+        interval: Duration,
+        count: usize,
+    ) -> Result<Vec<Candle>> {
+        let pool = self.config.pool(pool_key)?;
+        let pool_id = pool.id()?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let interval_ms = interval.as_millis() as u64;
+        if interval_ms == 0 || count == 0 {
+            return Err(anyhow!("get_ohlc needs a non-zero interval and count"));
+        }
+        let window_ms = interval_ms
+            .checked_mul(count as u64 - 1)
+            .ok_or_else(|| anyhow!("{} candles of {:?} span too long a window", count, interval))?;
+        let current_start = self.ctx.now_ms() / interval_ms * interval_ms;
+        let window_start = current_start.saturating_sub(window_ms);
+
+        let package_id = self.config.package_id()?;
+        let event_type = parse_sui_struct_tag(&format!("{}::order_info::OrderFilled", package_id))?;
+        let client = self.ctx.client()?;
+        let mut fills = vec![];
+        let mut cursor = None;
+        for _ in 0..MAX_EVENT_PAGES {
+            let page = self
+                .with_rpc_timeout(client.event_api().query_events(
+                    EventFilter::MoveEventType(event_type.clone()),
+                    cursor,
+                    Some(EVENTS_PER_PAGE),
+                    true,
+                ))
+                .await?;
+            let mut reached_start = false;
+            for event in &page.data {
+                let Some(DeepBookEvent::OrderFilled(fill)) = parse_event(event, package_id)? else {
+                    continue;
+                };
+                if fill.timestamp < window_start {
+                    reached_start = true;
+                    break;
+                }
+                if fill.pool_id == pool_id {
+                    fills.push((
+                        fill.timestamp,
+                        price_from_units(fill.price, base, quote),
+                        base.from_units(fill.base_quantity),
+                    ));
+                }
+            }
+            if reached_start || !page.has_next_page {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        fills.reverse();
+
+        let mut candles: Vec<Candle> = Vec::with_capacity(count);
+        let mut fills = fills.into_iter().peekable();
+        for bucket in 0..count as u64 {
+            let start_ms = window_start + bucket * interval_ms;
+            let end_ms = start_ms + interval_ms;
+            let mut candle: Option<Candle> = None;
+            while let Some((_, price, quantity)) =
+                fills.next_if(|(timestamp, _, _)| *timestamp < end_ms)
+            {
+                let candle = candle.get_or_insert(Candle {
+                    start_ms,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: 0.0,
+                });
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += quantity;
+            }
+
+            match (candle, candles.last()) {
+                (Some(candle), _) => candles.push(candle),
+                (None, Some(previous)) => {
+                    let close = previous.close;
+                    candles.push(Candle {
+                        start_ms,
+                        open: close,
+                        high: close,
+                        low: close,
+                        close,
+                        volume: 0.0,
+                    });
+                }
+                (None, None) => {}
+            }
+        }
+        Ok(candles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::offline_client;
+    use crate::context::BuildContext;
+
+    #[tokio::test]
+    async fn an_overflowing_window_is_rejected() {
+        let client = offline_client(BuildContext::fixed().with_clock(1_000));
+        let error = client
+            .get_ohlc("SUI_DBUSDC", Duration::from_millis(u64::MAX / 2), 4)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("too long a window"));
+    }
+}
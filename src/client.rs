@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use deepbookv3::types::BalanceManager;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+
+use crate::config::DeepBookConfig;
+use crate::context::BuildContext;
+use crate::error::ClientError;
+
+/// DeepBook client whose builders take every network-derived value from a `BuildContext`.
+pub struct TradingClient {
+    pub ctx: BuildContext,
+    pub sender: SuiAddress,
+    pub config: DeepBookConfig,
+    pub balance_managers: HashMap<String, BalanceManager>,
+    pub default_manager: Option<String>,
+    /// Dev-inspect every transaction before signing and refuse to submit it if it aborts.
+    pub preflight: bool,
+    /// Check wallet and manager balances before building orders, swaps and withdrawals.
+    pub balance_checks: bool,
+    /// Upper bound on each dev-inspect and execute call.
+    pub timeout: Duration,
+    /// Batched calls split their operations across transactions to stay within these.
+    pub max_commands_per_ptb: usize,
+    pub max_tx_bytes: usize,
+}
+
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sui's protocol limits on a single transaction.
+pub const DEFAULT_MAX_COMMANDS_PER_PTB: usize = 1024;
+pub const DEFAULT_MAX_TX_BYTES: usize = 128 * 1024;
+
+impl TradingClient {
+    pub fn new(
+        ctx: BuildContext,
+        sender: SuiAddress,
+        config: DeepBookConfig,
+        balance_managers: HashMap<String, BalanceManager>,
+    ) -> Self {
+        TradingClient {
+            ctx,
+            sender,
+            config,
+            balance_managers,
+            default_manager: None,
+            preflight: false,
+            balance_checks: false,
+            timeout: DEFAULT_RPC_TIMEOUT,
+            max_commands_per_ptb: DEFAULT_MAX_COMMANDS_PER_PTB,
+            max_tx_bytes: DEFAULT_MAX_TX_BYTES,
+        }
+    }
+
+    /// Use `manager_key` whenever a call omits its manager.
+    pub fn with_default_manager(mut self, manager_key: &str) -> Self {
+        self.default_manager = Some(manager_key.to_string());
+        self
+    }
+
+    pub fn with_preflight(mut self, preflight: bool) -> Self {
+        self.preflight = preflight;
+        self
+    }
+
+    pub fn with_balance_checks(mut self, balance_checks: bool) -> Self {
+        self.balance_checks = balance_checks;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Cap the commands and serialized size of each transaction a batched call submits.
+    pub fn with_ptb_limits(mut self, max_commands: usize, max_tx_bytes: usize) -> Self {
+        self.max_commands_per_ptb = max_commands;
+        self.max_tx_bytes = max_tx_bytes;
+        self
+    }
+
+    /// Await an RPC call, failing with `ClientError::Timeout` once `timeout` elapses.
+    pub async fn with_rpc_timeout<T, E, F>(&self, call: F) -> Result<T>
+    where
+        F: Future<Output = std::result::Result<T, E>>,
+        anyhow::Error: From<E>,
+    {
+        match tokio::time::timeout(self.timeout, call).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(ClientError::Timeout(self.timeout).into()),
+        }
+    }
+
+    /// The reference gas price the execute path uses, cached per the context's TTL.
+    pub async fn get_gas_price(&self) -> Result<u64> {
+        self.ctx.gas_price().await
+    }
+
+    /// Drop the cached gas price and fetch the current one.
+    pub async fn refresh_gas_price(&self) -> Result<u64> {
+        self.ctx.refresh_gas_price().await
+    }
+
+    /// Resolve an optional manager key against the default manager.
+    pub fn manager_key<'a>(&'a self, manager_key: Option<&'a str>) -> Result<&'a str> {
+        manager_key
+            .or(self.default_manager.as_deref())
+            .ok_or_else(|| ClientError::NoManagerSpecified.into())
+    }
+
+    /// Find the listed pool for a pair of coins; see `DeepBookConfig::find_pool`.
+    pub fn find_pool(&self, coin_a: &str, coin_b: &str) -> Result<Option<String>> {
+        self.config.find_pool(coin_a, coin_b)
+    }
+
+    pub fn balance_manager(&self, manager_key: &str) -> Result<&BalanceManager> {
+        self.balance_managers
+            .get(manager_key)
+            .ok_or_else(|| anyhow!("Balance manager not found for key: {}", manager_key))
+    }
+
+    pub fn balance_manager_id(&self, manager_key: &str) -> Result<ObjectID> {
+        let manager = self.balance_manager(manager_key)?;
+        Ok(ObjectID::from_hex_literal(&manager.address)?)
+    }
+
+    /// Resolve an optional manager key to the manager's object ID.
+    pub fn manager_id(&self, manager_key: Option<&str>) -> Result<ObjectID> {
+        self.balance_manager_id(self.manager_key(manager_key)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use sui_sdk::types::{
+        base_types::SequenceNumber,
+        transaction::{Command, ProgrammableTransaction},
+    };
+
+    use super::*;
+
+    pub const MANAGER_KEY: &str = "MANAGER";
+    pub const MANAGER_ID: &str =
+        "0x08933685e0246a2ddae2f5e5628fdeba09de831cadf5ad949db308807f18bee5";
+
+    /// A testnet client whose default manager and pools resolve without the network.
+    /// Everything else a test needs must be stubbed on `ctx`.
+    pub fn offline_client(ctx: BuildContext) -> TradingClient {
+        let config = DeepBookConfig::new("testnet").unwrap();
+        let mut ctx = ctx.with_shared_version(
+            ObjectID::from_hex_literal(MANAGER_ID).unwrap(),
+            SequenceNumber::from_u64(1),
+        );
+        for pool in config.pools.values() {
+            ctx = ctx.with_shared_version(pool.id().unwrap(), SequenceNumber::from_u64(1));
+        }
+        let balance_managers = HashMap::from([(
+            MANAGER_KEY.to_string(),
+            BalanceManager {
+                address: MANAGER_ID,
+                trade_cap: None,
+                deposit_cap: None,
+                withdraw_cap: None,
+            },
+        )]);
+        TradingClient::new(ctx, SuiAddress::ZERO, config, balance_managers)
+            .with_default_manager(MANAGER_KEY)
+    }
+
+    /// The `module::function` of every Move call in `pt`, in order.
+    pub fn move_call_names(pt: &ProgrammableTransaction) -> Vec<String> {
+        pt.commands
+            .iter()
+            .filter_map(|command| match command {
+                Command::MoveCall(call) => Some(format!("{}::{}", call.module, call.function)),
+                _ => None,
+            })
+            .collect()
+    }
+}
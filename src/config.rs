@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use sui_sdk::types::{TypeTag, base_types::ObjectID};
+
+/// Scaling used by DeepBook for prices and fees.
+pub const FLOAT_SCALAR: f64 = 1_000_000_000.0;
+
+/// Price limits enforced by `order_info::validate_inputs`, in on-chain units.
+pub const MIN_PRICE: u64 = 1;
+pub const MAX_PRICE: u64 = (1u64 << 63) - 1;
+
+/// On-chain objects every DeepBook call resolves its target through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageIds {
+    pub deepbook_package_id: String,
+    pub registry_id: String,
+    pub deep_treasury_id: String,
+}
+
+impl PackageIds {
+    /// The package IDs shipped with this release for `env`.
+    pub fn for_env(env: &str) -> Result<Self> {
+        let (deepbook_package_id, registry_id, deep_treasury_id) = match env {
+            "mainnet" => (
+                "0x2c8d603bc51326b8c13cef9dd07031a408a48dddb541963357661df5d3204809",
+                "0xaf16199a2dff736e9f07a845f23c5da6df6f756eddb631aed9d24a93efc4549d",
+                "0x032abf8948dda67a271bcc18e776dbbcfb0d58c8d288a700ff0d5521e57a1ffe",
+            ),
+            "testnet" => (
+                "0xcbf4748a965d469ea3a36cf0ccc5743b96c2d0ae6dee0762ed3eca65fac07f7e",
+                "0x7c256edbda983a2cd6f946655f4bf3f00a41043993781f8674a7046e8c0e11d1",
+                "0x69fffdae0075f8f71f4fa793549c11079266910e8905169845af1f5d00e09dcb",
+            ),
+            _ => return Err(anyhow!("Unsupported environment: {}", env)),
+        };
+        Ok(PackageIds {
+            deepbook_package_id: deepbook_package_id.to_string(),
+            registry_id: registry_id.to_string(),
+            deep_treasury_id: deep_treasury_id.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Coin {
+    pub address: String,
+    pub type_: String,
+    pub scalar: u64,
+}
+
+impl Coin {
+    pub fn new(address: &str, type_: &str, scalar: u64) -> Self {
+        Coin {
+            address: address.to_string(),
+            type_: type_.to_string(),
+            scalar,
+        }
+    }
+
+    pub fn type_tag(&self) -> Result<TypeTag> {
+        TypeTag::from_str(&self.type_)
+    }
+
+    /// Convert a human amount into the coin's smallest unit.
+    pub fn to_units(&self, amount: f64) -> u64 {
+        (amount * self.scalar as f64).round() as u64
+    }
+
+    /// Convert an amount in the coin's smallest unit into human units.
+    pub fn from_units(&self, amount: u64) -> f64 {
+        amount as f64 / self.scalar as f64
+    }
+
+    /// Number of decimal places the coin supports. Fails unless the scalar is a power
+    /// of ten, since no whole number of decimals describes any other scalar.
+    pub fn decimals(&self) -> Result<u32> {
+        self.scalar
+            .checked_ilog10()
+            .filter(|decimals| 10u64.pow(*decimals) == self.scalar)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Coin {} has scalar {}, which is not a power of ten",
+                    self.type_,
+                    self.scalar
+                )
+            })
+    }
+
+    /// Parse a decimal string such as "1234.56789" into the coin's smallest unit exactly.
+    pub fn parse_units(&self, amount: &str) -> Result<u64> {
+        parse_decimal(amount, self.decimals()?)
+    }
+}
+
+/// Convert a human price into DeepBook's on-chain price representation.
+pub fn price_to_units(price: f64, base: &Coin, quote: &Coin) -> u64 {
+    (price * FLOAT_SCALAR * quote.scalar as f64 / base.scalar as f64).round() as u64
+}
+
+/// Parse a decimal price string into DeepBook's on-chain price representation exactly.
+pub fn parse_price_units(price: &str, base: &Coin, quote: &Coin) -> Result<u64> {
+    // On-chain price = price * FLOAT_SCALAR * quote.scalar / base.scalar.
+    let decimals = 9 + quote.decimals()? as i64 - base.decimals()? as i64;
+    let decimals = u32::try_from(decimals)
+        .map_err(|_| anyhow!("Prices for this coin pair cannot be represented exactly"))?;
+    parse_decimal(price, decimals)
+}
+
+/// Parse a non-negative decimal string into an integer scaled by `10^decimals`, without
+/// going through `f64`. Trailing zeros are ignored, any other digit past `decimals`
+/// places is an error.
+pub fn parse_decimal(value: &str, decimals: u32) -> Result<u64> {
+    let value = value.trim();
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(anyhow!("Invalid decimal amount: {:?}", value));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(anyhow!(
+            "{} has more than {} decimal places",
+            value,
+            decimals
+        ));
+    }
+
+    let overflow = || anyhow!("{} is too large", value);
+    let scale = 10u64.checked_pow(decimals).ok_or_else(overflow)?;
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| overflow())?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u64>().map_err(|_| overflow())?
+            * 10u64.pow(decimals - fraction.len() as u32)
+    };
+    whole
+        .checked_mul(scale)
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or_else(overflow)
+}
+
+/// Convert an on-chain price into a human price.
+pub fn price_from_units(price: u64, base: &Coin, quote: &Coin) -> f64 {
+    price as f64 * base.scalar as f64 / (FLOAT_SCALAR * quote.scalar as f64)
+}
+
+#[derive(Debug, Clone)]
+pub struct Pool {
+    pub address: String,
+    pub base_coin: String,
+    pub quote_coin: String,
+}
+
+impl Pool {
+    fn new(address: &str, base_coin: &str, quote_coin: &str) -> Self {
+        Pool {
+            address: address.to_string(),
+            base_coin: base_coin.to_string(),
+            quote_coin: quote_coin.to_string(),
+        }
+    }
+
+    pub fn id(&self) -> Result<ObjectID> {
+        Ok(ObjectID::from_hex_literal(&self.address)?)
+    }
+}
+
+/// A complete coin table: every coin's type and decimals, plus aliases. Installing
+/// one with `DeepBookConfig::with_coin_registry` makes it the only source of coin
+/// metadata, so startup and unit scaling never depend on the network.
+#[derive(Debug, Clone, Default)]
+pub struct CoinRegistry {
+    pub coins: HashMap<String, Coin>,
+    pub aliases: HashMap<String, String>,
+}
+
+impl CoinRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a coin. Fails if its scalar is not a power of ten.
+    pub fn with_coin(mut self, key: &str, coin: Coin) -> Result<Self> {
+        coin.decimals()?;
+        self.coins.insert(key.to_string(), coin);
+        Ok(self)
+    }
+
+    pub fn with_alias(mut self, alias: &str, target: &str) -> Self {
+        self.aliases.insert(alias.to_string(), target.to_string());
+        self
+    }
+}
+
+/// Package IDs, coins and pools known to the client for one environment.
+#[derive(Debug, Clone)]
+pub struct DeepBookConfig {
+    pub env: String,
+    pub package_ids: PackageIds,
+    pub coins: HashMap<String, Coin>,
+    /// Extra names for coins, mapping to a coin key or a full coin type.
+    pub aliases: HashMap<String, String>,
+    pub pools: HashMap<String, Pool>,
+}
+
+impl DeepBookConfig {
+    pub fn new(env: &str) -> Result<Self> {
+        let (coins, pools) = match env {
+            "mainnet" => (mainnet_coins(), mainnet_pools()),
+            "testnet" => (testnet_coins(), testnet_pools()),
+            _ => return Err(anyhow!("Unsupported environment: {}", env)),
+        };
+        Ok(DeepBookConfig {
+            env: env.to_string(),
+            package_ids: PackageIds::for_env(env)?,
+            coins,
+            aliases: HashMap::new(),
+            pools,
+        })
+    }
+
+    /// Replace the built-in package IDs, e.g. when DeepBook ships an upgrade
+    /// before a new release of this client.
+    pub fn with_package_ids(mut self, package_ids: PackageIds) -> Self {
+        self.package_ids = package_ids;
+        self
+    }
+
+    pub fn set_package_ids(&mut self, package_ids: PackageIds) {
+        self.package_ids = package_ids;
+    }
+
+    /// Add a coin or replace a built-in one. Fails if its scalar is not a power of ten.
+    pub fn with_coin(mut self, key: &str, coin: Coin) -> Result<Self> {
+        coin.decimals()?;
+        self.coins.insert(key.to_string(), coin);
+        Ok(self)
+    }
+
+    /// Replace the built-in coins and any aliases with `registry`. Pools whose coins
+    /// the registry does not list fail to resolve rather than falling back. Fails if
+    /// any coin's scalar is not a power of ten.
+    pub fn with_coin_registry(mut self, registry: CoinRegistry) -> Result<Self> {
+        for coin in registry.coins.values() {
+            coin.decimals()?;
+        }
+        self.coins = registry.coins;
+        self.aliases = registry.aliases;
+        Ok(self)
+    }
+
+    /// The coins and aliases currently known, e.g. to persist and reload them later.
+    pub fn coin_registry(&self) -> CoinRegistry {
+        CoinRegistry {
+            coins: self.coins.clone(),
+            aliases: self.aliases.clone(),
+        }
+    }
+
+    /// Register `alias` as another name for `target`, a coin key or full coin type.
+    pub fn with_alias(mut self, alias: &str, target: &str) -> Self {
+        self.aliases.insert(alias.to_string(), target.to_string());
+        self
+    }
+
+    /// Look up a coin by key, alias or full type string.
+    pub fn coin(&self, key: &str) -> Result<&Coin> {
+        self.coin_key(key)
+            .map(|key| &self.coins[key])
+            .ok_or_else(|| anyhow!("Coin not found for key: {}", key))
+    }
+
+    /// Resolve a coin key, alias or full type string to the coin's key.
+    pub fn coin_key<'a>(&'a self, coin: &'a str) -> Option<&'a str> {
+        if self.coins.contains_key(coin) {
+            return Some(coin);
+        }
+        let coin = self.aliases.get(coin).map(String::as_str).unwrap_or(coin);
+        if let Some((key, _)) = self.coins.get_key_value(coin) {
+            return Some(key);
+        }
+        let type_ = TypeTag::from_str(coin).ok()?.to_canonical_string(true);
+        self.coins
+            .iter()
+            .find(|(_, known)| {
+                TypeTag::from_str(&known.type_)
+                    .is_ok_and(|known| known.to_canonical_string(true) == type_)
+            })
+            .map(|(key, _)| key.as_str())
+    }
+
+    pub fn pool(&self, key: &str) -> Result<&Pool> {
+        self.pools
+            .get(key)
+            .ok_or_else(|| anyhow!("Pool not found for key: {}", key))
+    }
+
+    /// Canonical type string of a coin given its key, an alias or its type. Types of
+    /// coins the config does not list are passed through.
+    pub fn coin_type(&self, coin: &str) -> Result<String> {
+        let coin = self.aliases.get(coin).map(String::as_str).unwrap_or(coin);
+        let type_ = match self.coins.get(coin) {
+            Some(known) => known.type_.as_str(),
+            None => coin,
+        };
+        Ok(TypeTag::from_str(type_)?.to_canonical_string(true))
+    }
+
+    /// Find the listed pool trading `coin_a` against `coin_b`, in either orientation.
+    /// Coins may be given as keys or full type strings. The pool's `base_coin` tells
+    /// which of the two is the base.
+    pub fn find_pool(&self, coin_a: &str, coin_b: &str) -> Result<Option<String>> {
+        let coin_a = self.coin_type(coin_a)?;
+        let coin_b = self.coin_type(coin_b)?;
+
+        let mut pool_keys: Vec<&String> = self.pools.keys().collect();
+        pool_keys.sort();
+        for pool_key in pool_keys {
+            let pool = &self.pools[pool_key];
+            let base = self.coin_type(&pool.base_coin)?;
+            let quote = self.coin_type(&pool.quote_coin)?;
+            if (base == coin_a && quote == coin_b) || (base == coin_b && quote == coin_a) {
+                return Ok(Some(pool_key.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn package_id(&self) -> Result<ObjectID> {
+        Ok(ObjectID::from_hex_literal(
+            &self.package_ids.deepbook_package_id,
+        )?)
+    }
+
+    pub fn registry_id(&self) -> Result<ObjectID> {
+        Ok(ObjectID::from_hex_literal(&self.package_ids.registry_id)?)
+    }
+
+    pub fn deep_treasury_id(&self) -> Result<ObjectID> {
+        Ok(ObjectID::from_hex_literal(
+            &self.package_ids.deep_treasury_id,
+        )?)
+    }
+}
+
+fn mainnet_coins() -> HashMap<String, Coin> {
+    HashMap::from([
+        (
+            "DEEP".to_string(),
+            Coin::new(
+                "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270",
+                "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP",
+                1_000_000,
+            ),
+        ),
+        (
+            "SUI".to_string(),
+            Coin::new(
+                "0x0000000000000000000000000000000000000000000000000000000000000002",
+                "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI",
+                1_000_000_000,
+            ),
+        ),
+        (
+            "USDC".to_string(),
+            Coin::new(
+                "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7",
+                "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+                1_000_000,
+            ),
+        ),
+        (
+            "WUSDC".to_string(),
+            Coin::new(
+                "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf",
+                "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN",
+                1_000_000,
+            ),
+        ),
+        (
+            "WUSDT".to_string(),
+            Coin::new(
+                "0xc060006111016b8a020ad5b33834984a437aaa7d3c74c18e09a95d48aceab08c",
+                "0xc060006111016b8a020ad5b33834984a437aaa7d3c74c18e09a95d48aceab08c::coin::COIN",
+                1_000_000,
+            ),
+        ),
+        (
+            "NS".to_string(),
+            Coin::new(
+                "0x5145494a5f5100e645e4b0aa950fa6b68f614e8c59e17bc5ded3495123a79178",
+                "0x5145494a5f5100e645e4b0aa950fa6b68f614e8c59e17bc5ded3495123a79178::ns::NS",
+                1_000_000,
+            ),
+        ),
+        (
+            "WAL".to_string(),
+            Coin::new(
+                "0x356a26eb9e012a68958082340d4c4116e7f55615cf27affcff209cf0ae544f59",
+                "0x356a26eb9e012a68958082340d4c4116e7f55615cf27affcff209cf0ae544f59::wal::WAL",
+                1_000_000_000,
+            ),
+        ),
+    ])
+}
+
+fn mainnet_pools() -> HashMap<String, Pool> {
+    HashMap::from([
+        (
+            "DEEP_SUI".to_string(),
+            Pool::new(
+                "0xb663828d6217467c8a1838a03793da896cbe745b150ebd57d82f814ca579fc22",
+                "DEEP",
+                "SUI",
+            ),
+        ),
+        (
+            "SUI_USDC".to_string(),
+            Pool::new(
+                "0xe05dafb5133bcffb8d59f4e12465dc0e9faeaa05e3e342a08fe135800e3e4407",
+                "SUI",
+                "USDC",
+            ),
+        ),
+        (
+            "DEEP_USDC".to_string(),
+            Pool::new(
+                "0xf948981b806057580f91622417534f491da5f61aeaf33d0ed8e69fd5691c95ce",
+                "DEEP",
+                "USDC",
+            ),
+        ),
+        (
+            "WUSDT_USDC".to_string(),
+            Pool::new(
+                "0x4e2ca3988246e1d50b9bf209abb9c1cbfec65bd95afdacc620a36c67bdb8452f",
+                "WUSDT",
+                "USDC",
+            ),
+        ),
+        (
+            "WUSDC_USDC".to_string(),
+            Pool::new(
+                "0xa0b9ebefb38c963fd115f52d71fa64501b79d1adcb5270563f92ce0442376545",
+                "WUSDC",
+                "USDC",
+            ),
+        ),
+        (
+            "NS_USDC".to_string(),
+            Pool::new(
+                "0x0c0fdd4008740d81a8a7d4281322aee71a1b62c449eb5b142656753d89ebc060",
+                "NS",
+                "USDC",
+            ),
+        ),
+        (
+            "NS_SUI".to_string(),
+            Pool::new(
+                "0x27c4fdb3b846aa3ae4a65ef5127a309aa3c1f466671471a806d8912a18b253e8",
+                "NS",
+                "SUI",
+            ),
+        ),
+        (
+            "WAL_USDC".to_string(),
+            Pool::new(
+                "0x56a1c985c1f1123181d6b881714793689321ba24301b3585eec427436eb1c76d",
+                "WAL",
+                "USDC",
+            ),
+        ),
+        (
+            "WAL_SUI".to_string(),
+            Pool::new(
+                "0x81f5339934c83ea19dd6bcc75c52e83509629a5f71d3257428c2ce47cc94d08b",
+                "WAL",
+                "SUI",
+            ),
+        ),
+    ])
+}
+
+fn testnet_coins() -> HashMap<String, Coin> {
+    HashMap::from([
+        (
+            "DEEP".to_string(),
+            Coin::new(
+                "0x36dbef866a1d62bf7328989a10fb2f07d769f4ee587c0de4a0a256e57e0a58a8",
+                "0x36dbef866a1d62bf7328989a10fb2f07d769f4ee587c0de4a0a256e57e0a58a8::deep::DEEP",
+                1_000_000,
+            ),
+        ),
+        (
+            "SUI".to_string(),
+            Coin::new(
+                "0x0000000000000000000000000000000000000000000000000000000000000002",
+                "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI",
+                1_000_000_000,
+            ),
+        ),
+        (
+            "DBUSDC".to_string(),
+            Coin::new(
+                "0xf7152c05930480cd740d7311b5b8b45c6f488e3a53a11c3f74a6fac36a52e0d7",
+                "0xf7152c05930480cd740d7311b5b8b45c6f488e3a53a11c3f74a6fac36a52e0d7::DBUSDC::DBUSDC",
+                1_000_000,
+            ),
+        ),
+        (
+            "DBUSDT".to_string(),
+            Coin::new(
+                "0xf7152c05930480cd740d7311b5b8b45c6f488e3a53a11c3f74a6fac36a52e0d7",
+                "0xf7152c05930480cd740d7311b5b8b45c6f488e3a53a11c3f74a6fac36a52e0d7::DBUSDT::DBUSDT",
+                1_000_000,
+            ),
+        ),
+    ])
+}
+
+fn testnet_pools() -> HashMap<String, Pool> {
+    HashMap::from([
+        (
+            "DEEP_SUI".to_string(),
+            Pool::new(
+                "0x0d1b1746d220bd5ebac5231c7685480a16f1c707a46306095a4c67dc7ce4dcae",
+                "DEEP",
+                "SUI",
+            ),
+        ),
+        (
+            "SUI_DBUSDC".to_string(),
+            Pool::new(
+                "0x520c89c6c78c566eed0ebf24f854a8c22d8fdd06a6f16ad01f108dad7f1baaea",
+                "SUI",
+                "DBUSDC",
+            ),
+        ),
+        (
+            "DEEP_DBUSDC".to_string(),
+            Pool::new(
+                "0xe86b991f8632217505fd859445f9803967ac84a9d4a1219065bf191fcb74b622",
+                "DEEP",
+                "DBUSDC",
+            ),
+        ),
+        (
+            "DBUSDT_DBUSDC".to_string(),
+            Pool::new(
+                "0x83970bb02e3636efdff8c141ab06af5e3c9a22e2f74d7f02a9c3430d0d10c1ca",
+                "DBUSDT",
+                "DBUSDC",
+            ),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(scalar: u64) -> Coin {
+        Coin::new("0x2", "0x2::sui::SUI", scalar)
+    }
+
+    #[test]
+    fn decimals_of_power_of_ten_scalars() {
+        assert_eq!(coin(1).decimals().unwrap(), 0);
+        assert_eq!(coin(1_000_000).decimals().unwrap(), 6);
+        assert_eq!(coin(10u64.pow(19)).decimals().unwrap(), 19);
+    }
+
+    #[test]
+    fn decimals_reject_other_scalars() {
+        assert!(coin(0).decimals().is_err());
+        assert!(coin(1_500_000).decimals().is_err());
+        assert!(coin(u64::MAX).decimals().is_err());
+    }
+
+    #[test]
+    fn registering_a_bad_scalar_fails() {
+        assert!(CoinRegistry::new().with_coin("BAD", coin(0)).is_err());
+        let config = DeepBookConfig::new("testnet").unwrap();
+        assert!(config.clone().with_coin("BAD", coin(2_000)).is_err());
+        let registry = config
+            .coin_registry()
+            .with_coin("SUI", coin(1_000_000_000))
+            .unwrap();
+        let mut bad = registry.clone();
+        bad.coins.insert("BAD".to_string(), coin(3));
+        assert!(config.clone().with_coin_registry(bad).is_err());
+        assert!(config.with_coin_registry(registry).is_ok());
+    }
+
+    #[test]
+    fn parse_decimal_is_exact() {
+        assert_eq!(parse_decimal("1234.56789", 5).unwrap(), 123_456_789);
+        assert_eq!(parse_decimal("0.1", 9).unwrap(), 100_000_000);
+        assert_eq!(parse_decimal(".5", 1).unwrap(), 5);
+        assert_eq!(parse_decimal("7.", 2).unwrap(), 700);
+        assert_eq!(parse_decimal(" 2.500 ", 1).unwrap(), 25);
+        assert_eq!(parse_decimal("18446744073709551615", 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn parse_decimal_rejects_bad_input() {
+        for value in ["", ".", "-1", "1e3", "1.2.3", "abc"] {
+            assert!(parse_decimal(value, 6).is_err(), "{:?} parsed", value);
+        }
+        assert!(parse_decimal("0.1234567", 6).is_err());
+        assert!(parse_decimal("18446744073709551616", 0).is_err());
+        assert!(parse_decimal("18446744073.709551616", 9).is_err());
+    }
+
+    #[test]
+    fn price_units_account_for_both_coins() {
+        let config = DeepBookConfig::new("testnet").unwrap();
+        let (sui, dbusdc) = (config.coin("SUI").unwrap(), config.coin("DBUSDC").unwrap());
+        assert_eq!(parse_price_units("2.5", sui, dbusdc).unwrap(), 2_500_000);
+        assert_eq!(price_to_units(2.5, sui, dbusdc), 2_500_000);
+        assert_eq!(price_from_units(2_500_000, sui, dbusdc), 2.5);
+        assert!(parse_price_units("2.0000005", sui, dbusdc).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+use sui_sdk::{
+    SuiClient,
+    rpc_types::SuiObjectDataOptions,
+    types::{
+        SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION,
+        base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress},
+        object::Owner,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        transaction::{Argument, Command, ObjectArg, ProgrammableTransaction, TransactionData},
+    },
+};
+
+use crate::config::Coin;
+use crate::types::{Account, Balances, Order, PoolBookParams};
+
+/// Everything a transaction builder would otherwise fetch from the network.
+///
+/// A live context resolves values through the `SuiClient`; anything set with the
+/// `with_*` methods takes precedence, so a context built with `BuildContext::fixed()`
+/// produces the exact same PTB on every run without touching the RPC.
+#[derive(Clone)]
+pub struct BuildContext {
+    client: Option<SuiClient>,
+    clock_timestamp_ms: Option<u64>,
+    gas_price: Option<u64>,
+    /// Fetched reference gas prices are reused for this long; `None` disables caching.
+    gas_price_ttl: Option<Duration>,
+    /// Shared between clones, so every builder on a context sees one cached price.
+    cached_gas_price: Arc<Mutex<Option<(u64, Instant)>>>,
+    shared_versions: HashMap<ObjectID, SequenceNumber>,
+    owned_objects: HashMap<ObjectID, ObjectRef>,
+    whitelisted_pools: HashMap<ObjectID, bool>,
+    book_params: HashMap<ObjectID, PoolBookParams>,
+    /// Keyed by (pool, manager); `None` stands for a manager with no account on the pool.
+    accounts: HashMap<(ObjectID, ObjectID), Option<Account>>,
+    orders: HashMap<(ObjectID, u128), Order>,
+    /// Keyed by (pool, manager), in on-chain units.
+    locked_balances: HashMap<(ObjectID, ObjectID), Balances>,
+    coins: HashMap<String, Vec<ObjectRef>>,
+    balances: HashMap<String, u128>,
+    gas_coins: Option<Vec<ObjectRef>>,
+}
+
+impl BuildContext {
+    pub fn live(client: SuiClient) -> Self {
+        BuildContext {
+            client: Some(client),
+            ..Self::fixed()
+        }
+    }
+
+    /// A context with no network access; every value must be supplied up front.
+    pub fn fixed() -> Self {
+        BuildContext {
+            client: None,
+            clock_timestamp_ms: None,
+            gas_price: None,
+            gas_price_ttl: None,
+            cached_gas_price: Arc::new(Mutex::new(None)),
+            shared_versions: HashMap::new(),
+            owned_objects: HashMap::new(),
+            whitelisted_pools: HashMap::new(),
+            book_params: HashMap::new(),
+            accounts: HashMap::new(),
+            orders: HashMap::new(),
+            locked_balances: HashMap::new(),
+            coins: HashMap::new(),
+            balances: HashMap::new(),
+            gas_coins: None,
+        }
+    }
+
+    pub fn with_clock(mut self, timestamp_ms: u64) -> Self {
+        self.clock_timestamp_ms = Some(timestamp_ms);
+        self
+    }
+
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
+    /// Cache the fetched reference gas price for `ttl`. It only changes at epoch
+    /// boundaries, so a TTL of a few minutes saves a round trip per transaction.
+    pub fn with_gas_price_ttl(mut self, ttl: Duration) -> Self {
+        self.gas_price_ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_shared_version(
+        mut self,
+        id: ObjectID,
+        initial_shared_version: SequenceNumber,
+    ) -> Self {
+        self.shared_versions.insert(id, initial_shared_version);
+        self
+    }
+
+    pub fn with_owned_object(mut self, object_ref: ObjectRef) -> Self {
+        self.owned_objects.insert(object_ref.0, object_ref);
+        self
+    }
+
+    pub fn with_whitelisted(mut self, pool_id: ObjectID, whitelisted: bool) -> Self {
+        self.whitelisted_pools.insert(pool_id, whitelisted);
+        self
+    }
+
+    pub fn with_book_params(mut self, pool_id: ObjectID, book_params: PoolBookParams) -> Self {
+        self.book_params.insert(pool_id, book_params);
+        self
+    }
+
+    pub fn with_account(
+        mut self,
+        pool_id: ObjectID,
+        manager_id: ObjectID,
+        account: Option<Account>,
+    ) -> Self {
+        self.accounts.insert((pool_id, manager_id), account);
+        self
+    }
+
+    pub fn with_order(mut self, pool_id: ObjectID, order: Order) -> Self {
+        self.orders.insert((pool_id, order.order_id), order);
+        self
+    }
+
+    pub fn with_locked_balance(
+        mut self,
+        pool_id: ObjectID,
+        manager_id: ObjectID,
+        locked: Balances,
+    ) -> Self {
+        self.locked_balances.insert((pool_id, manager_id), locked);
+        self
+    }
+
+    pub fn with_coins(mut self, coin_type: &str, coins: Vec<ObjectRef>) -> Self {
+        self.coins.insert(coin_type.to_string(), coins);
+        self
+    }
+
+    pub fn with_balance(mut self, coin_type: &str, balance: u128) -> Self {
+        self.balances.insert(coin_type.to_string(), balance);
+        self
+    }
+
+    pub fn with_gas_coins(mut self, gas_coins: Vec<ObjectRef>) -> Self {
+        self.gas_coins = Some(gas_coins);
+        self
+    }
+
+    /// The stubbed whitelist status of a pool, if one was supplied.
+    pub fn whitelisted(&self, pool_id: ObjectID) -> Option<bool> {
+        self.whitelisted_pools.get(&pool_id).copied()
+    }
+
+    /// The stubbed book params of a pool, if they were supplied.
+    pub fn book_params(&self, pool_id: ObjectID) -> Option<PoolBookParams> {
+        self.book_params.get(&pool_id).copied()
+    }
+
+    /// The stubbed account of a manager on a pool, if one was supplied.
+    pub fn account(&self, pool_id: ObjectID, manager_id: ObjectID) -> Option<Option<Account>> {
+        self.accounts.get(&(pool_id, manager_id)).cloned()
+    }
+
+    /// The stubbed resting order on a pool, if one was supplied.
+    pub fn order(&self, pool_id: ObjectID, order_id: u128) -> Option<Order> {
+        self.orders.get(&(pool_id, order_id)).cloned()
+    }
+
+    /// The stubbed `pool::locked_balance` of a manager on a pool, if one was supplied.
+    pub fn locked_balance(&self, pool_id: ObjectID, manager_id: ObjectID) -> Option<Balances> {
+        self.locked_balances.get(&(pool_id, manager_id)).copied()
+    }
+
+    pub fn client(&self) -> Result<&SuiClient> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| anyhow!("No SuiClient available in a fixed build context"))
+    }
+
+    /// Current time in milliseconds, as seen by the builders.
+    pub fn now_ms(&self) -> u64 {
+        self.clock_timestamp_ms.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn clock(&self) -> ObjectArg {
+        ObjectArg::SharedObject {
+            id: SUI_CLOCK_OBJECT_ID,
+            initial_shared_version: SUI_CLOCK_OBJECT_SHARED_VERSION,
+            mutable: false,
+        }
+    }
+
+    /// The reference gas price: the stubbed one if set, else a cached one younger than
+    /// the TTL, else a fresh one from the network.
+    pub async fn gas_price(&self) -> Result<u64> {
+        if let Some(gas_price) = self.gas_price {
+            return Ok(gas_price);
+        }
+        if let Some(ttl) = self.gas_price_ttl {
+            if let Some((gas_price, fetched_at)) = *self.cached_gas_price.lock().unwrap() {
+                if fetched_at.elapsed() < ttl {
+                    return Ok(gas_price);
+                }
+            }
+        }
+        self.refresh_gas_price().await
+    }
+
+    /// Fetch the reference gas price, replacing any cached value, e.g. after an epoch
+    /// change.
+    pub async fn refresh_gas_price(&self) -> Result<u64> {
+        let gas_price = self.client()?.read_api().get_reference_gas_price().await?;
+        *self.cached_gas_price.lock().unwrap() = Some((gas_price, Instant::now()));
+        Ok(gas_price)
+    }
+
+    pub async fn shared_object(&self, id: ObjectID, mutable: bool) -> Result<ObjectArg> {
+        let initial_shared_version = match self.shared_versions.get(&id) {
+            Some(version) => *version,
+            None => {
+                let response = self
+                    .client()?
+                    .read_api()
+                    .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+                    .await?;
+                match response.data.and_then(|data| data.owner) {
+                    Some(Owner::Shared {
+                        initial_shared_version,
+                    }) => initial_shared_version,
+                    _ => return Err(anyhow!("Object {} is not a shared object", id)),
+                }
+            }
+        };
+
+        Ok(ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        })
+    }
+
+    pub async fn owned_object(&self, id: ObjectID) -> Result<ObjectArg> {
+        if let Some(object_ref) = self.owned_objects.get(&id) {
+            return Ok(ObjectArg::ImmOrOwnedObject(*object_ref));
+        }
+        let response = self
+            .client()?
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new())
+            .await?;
+        let data = response
+            .data
+            .ok_or_else(|| anyhow!("Object {} not found", id))?;
+        Ok(ObjectArg::ImmOrOwnedObject(data.object_ref()))
+    }
+
+    pub async fn coins(&self, owner: SuiAddress, coin_type: &str) -> Result<Vec<ObjectRef>> {
+        if let Some(coins) = self.coins.get(coin_type) {
+            return Ok(coins.clone());
+        }
+        let coins = self
+            .client()?
+            .coin_read_api()
+            .get_coins(owner, Some(coin_type.to_string()), None, None)
+            .await?;
+        Ok(coins.data.iter().map(|coin| coin.object_ref()).collect())
+    }
+
+    /// Total balance of `coin_type` owned by `owner`, in the coin's smallest unit.
+    pub async fn balance(&self, owner: SuiAddress, coin_type: &str) -> Result<u128> {
+        if let Some(balance) = self.balances.get(coin_type) {
+            return Ok(*balance);
+        }
+        let balance = self
+            .client()?
+            .coin_read_api()
+            .get_balance(owner, Some(coin_type.to_string()))
+            .await?;
+        Ok(balance.total_balance)
+    }
+
+    pub async fn gas_coins(&self, owner: SuiAddress) -> Result<Vec<ObjectRef>> {
+        if let Some(gas_coins) = &self.gas_coins {
+            return Ok(gas_coins.clone());
+        }
+        self.coins(owner, "0x2::sui::SUI").await
+    }
+
+    /// Split `amount` of `coin` off the owner's coins, merging them first if needed.
+    /// SUI is split from the gas coin.
+    pub async fn coin_with_balance(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        owner: SuiAddress,
+        coin: &Coin,
+        amount: u64,
+    ) -> Result<Argument> {
+        let amount = ptb.pure(amount)?;
+        if coin.type_.ends_with("::sui::SUI") {
+            let split = ptb.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+            return Ok(first_result(split));
+        }
+
+        let coins = self.coins(owner, &coin.type_).await?;
+        let (first, rest) = coins
+            .split_first()
+            .ok_or_else(|| anyhow!("No {} coins owned by {}", coin.type_, owner))?;
+        let primary = ptb.obj(ObjectArg::ImmOrOwnedObject(*first))?;
+        if !rest.is_empty() {
+            let others = rest
+                .iter()
+                .map(|coin| ptb.obj(ObjectArg::ImmOrOwnedObject(*coin)))
+                .collect::<Result<Vec<_>>>()?;
+            ptb.command(Command::MergeCoins(primary, others));
+        }
+        let split = ptb.command(Command::SplitCoins(primary, vec![amount]));
+        Ok(first_result(split))
+    }
+
+    pub async fn transaction_data(
+        &self,
+        sender: SuiAddress,
+        pt: ProgrammableTransaction,
+        gas_budget: u64,
+    ) -> Result<TransactionData> {
+        let gas_coins = self.gas_coins(sender).await?;
+        let gas_price = self.gas_price().await?;
+        Ok(TransactionData::new_programmable(
+            sender, gas_coins, pt, gas_budget, gas_price,
+        ))
+    }
+}
+
+/// The first output of a command, e.g. the coin produced by a single-amount split.
+pub fn first_result(argument: Argument) -> Argument {
+    match argument {
+        Argument::Result(index) => Argument::NestedResult(index, 0),
+        other => other,
+    }
+}
+
+/// The `index`th value of a command that returns a tuple.
+pub fn nested_result(argument: Argument, index: u16) -> Argument {
+    match argument {
+        Argument::Result(command) => Argument::NestedResult(command, index),
+        other => other,
+    }
+}
+
+/// A stable, line-per-command rendering of a PTB, suitable for snapshot comparisons.
+pub fn command_snapshot(pt: &ProgrammableTransaction) -> Vec<String> {
+    pt.commands
+        .iter()
+        .enumerate()
+        .map(|(i, cmd)| format!("[{}] {:?}", i, cmd))
+        .collect()
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Errors callers may want to match on; everything else is reported through `anyhow`.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The RPC provider does not serve `dev_inspect_transaction_block`.
+    DevInspectUnavailable(String),
+    /// No manager key was passed and the client has no default manager.
+    NoManagerSpecified,
+    /// An RPC call did not complete within the client's timeout.
+    Timeout(Duration),
+    /// A wallet or manager holds less of a coin than an operation needs, in human units.
+    InsufficientBalance { coin: String, have: f64, need: f64 },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::DevInspectUnavailable(message) => write!(
+                f,
+                "dev_inspect_transaction_block is not available on this RPC provider: {}",
+                message
+            ),
+            ClientError::NoManagerSpecified => write!(
+                f,
+                "no balance manager specified and no default manager configured"
+            ),
+            ClientError::Timeout(timeout) => {
+                write!(f, "RPC call timed out after {:?}", timeout)
+            }
+            ClientError::InsufficientBalance { coin, have, need } => {
+                write!(
+                    f,
+                    "insufficient {} balance: need {}, have {}",
+                    coin, need, have
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
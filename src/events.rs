@@ -0,0 +1,429 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+use serde_derive::Deserialize;
+use sui_sdk::{
+    rpc_types::{EventFilter, SuiEvent, SuiTransactionBlockResponse},
+    types::base_types::{ObjectID, SuiAddress},
+};
+
+use crate::client::TradingClient;
+
+/// Emitted when an order is filled; quantities and price are in on-chain units.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OrderFilled {
+    pub pool_id: ObjectID,
+    #[serde(deserialize_with = "from_str")]
+    pub maker_order_id: u128,
+    #[serde(deserialize_with = "from_str")]
+    pub taker_order_id: u128,
+    #[serde(deserialize_with = "from_str")]
+    pub maker_client_order_id: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub taker_client_order_id: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub price: u64,
+    pub taker_is_bid: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub taker_fee: u64,
+    pub taker_fee_is_deep: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub maker_fee: u64,
+    pub maker_fee_is_deep: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub base_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub quote_quantity: u64,
+    pub maker_balance_manager_id: ObjectID,
+    pub taker_balance_manager_id: ObjectID,
+    #[serde(deserialize_with = "from_str")]
+    pub timestamp: u64,
+}
+
+/// Emitted when an order rests on the book.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OrderPlaced {
+    pub balance_manager_id: ObjectID,
+    pub pool_id: ObjectID,
+    #[serde(deserialize_with = "from_str")]
+    pub order_id: u128,
+    #[serde(deserialize_with = "from_str")]
+    pub client_order_id: u64,
+    pub trader: SuiAddress,
+    #[serde(deserialize_with = "from_str")]
+    pub price: u64,
+    pub is_bid: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub placed_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub expire_timestamp: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub timestamp: u64,
+}
+
+/// Emitted when an order is cancelled by its owner.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OrderCanceled {
+    pub balance_manager_id: ObjectID,
+    pub pool_id: ObjectID,
+    #[serde(deserialize_with = "from_str")]
+    pub order_id: u128,
+    #[serde(deserialize_with = "from_str")]
+    pub client_order_id: u64,
+    pub trader: SuiAddress,
+    #[serde(deserialize_with = "from_str")]
+    pub price: u64,
+    pub is_bid: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub original_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub base_asset_quantity_canceled: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub timestamp: u64,
+}
+
+/// Emitted when an expired order is removed from the book during matching.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OrderExpired {
+    pub balance_manager_id: ObjectID,
+    pub pool_id: ObjectID,
+    #[serde(deserialize_with = "from_str")]
+    pub order_id: u128,
+    #[serde(deserialize_with = "from_str")]
+    pub client_order_id: u64,
+    pub trader: SuiAddress,
+    #[serde(deserialize_with = "from_str")]
+    pub price: u64,
+    pub is_bid: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub original_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub base_asset_quantity_canceled: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub timestamp: u64,
+}
+
+/// Emitted when an order's quantity is modified.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OrderModified {
+    pub balance_manager_id: ObjectID,
+    pub pool_id: ObjectID,
+    #[serde(deserialize_with = "from_str")]
+    pub order_id: u128,
+    #[serde(deserialize_with = "from_str")]
+    pub client_order_id: u64,
+    pub trader: SuiAddress,
+    #[serde(deserialize_with = "from_str")]
+    pub price: u64,
+    pub is_bid: bool,
+    #[serde(deserialize_with = "from_str")]
+    pub previous_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub filled_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub new_quantity: u64,
+    #[serde(deserialize_with = "from_str")]
+    pub timestamp: u64,
+}
+
+/// Emitted whenever funds enter or leave a balance manager, including the settlement
+/// of a pool's balances into it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BalanceEvent {
+    pub balance_manager_id: ObjectID,
+    /// Type of the coin moved, as `0x`-prefixed address, module and name.
+    #[serde(deserialize_with = "type_name")]
+    pub asset: String,
+    #[serde(deserialize_with = "from_str")]
+    pub amount: u64,
+    pub deposit: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepBookEvent {
+    OrderPlaced(OrderPlaced),
+    OrderCanceled(OrderCanceled),
+    OrderModified(OrderModified),
+    OrderFilled(OrderFilled),
+    OrderExpired(OrderExpired),
+    Balance(BalanceEvent),
+}
+
+impl DeepBookEvent {
+    /// Whether the event concerns an order of `balance_manager_id`, on either side of
+    /// a fill.
+    pub fn involves_manager(&self, balance_manager_id: ObjectID) -> bool {
+        match self {
+            DeepBookEvent::OrderPlaced(event) => event.balance_manager_id == balance_manager_id,
+            DeepBookEvent::OrderCanceled(event) => event.balance_manager_id == balance_manager_id,
+            DeepBookEvent::OrderModified(event) => event.balance_manager_id == balance_manager_id,
+            DeepBookEvent::OrderExpired(event) => event.balance_manager_id == balance_manager_id,
+            DeepBookEvent::Balance(event) => event.balance_manager_id == balance_manager_id,
+            DeepBookEvent::OrderFilled(event) => {
+                event.maker_balance_manager_id == balance_manager_id
+                    || event.taker_balance_manager_id == balance_manager_id
+            }
+        }
+    }
+}
+
+/// A lifecycle event of one manager's orders, tagged with the pool it happened on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagerEvent {
+    pub pool_key: String,
+    pub event: DeepBookEvent,
+}
+
+/// Decode a single event, returning `None` for events DeepBook does not emit. Only
+/// events whose type is defined by `package_id` are decoded, so a look-alike module of
+/// another package is never mistaken for DeepBook's.
+pub fn parse_event(event: &SuiEvent, package_id: ObjectID) -> Result<Option<DeepBookEvent>> {
+    if ObjectID::from(event.type_.address) != package_id {
+        return Ok(None);
+    }
+    let module = event.type_.module.as_str();
+    if module == "balance_manager" && event.type_.name.as_str() == "BalanceEvent" {
+        return Ok(Some(DeepBookEvent::Balance(decode(event)?)));
+    }
+    if module != "order_info" && module != "order" {
+        return Ok(None);
+    }
+
+    let parsed = match event.type_.name.as_str() {
+        "OrderPlaced" => DeepBookEvent::OrderPlaced(decode(event)?),
+        "OrderCanceled" => DeepBookEvent::OrderCanceled(decode(event)?),
+        "OrderModified" => DeepBookEvent::OrderModified(decode(event)?),
+        "OrderFilled" => DeepBookEvent::OrderFilled(decode(event)?),
+        "OrderExpired" => DeepBookEvent::OrderExpired(decode(event)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(parsed))
+}
+
+/// Decode every DeepBook event emitted by a transaction, in emission order.
+/// The response must have been requested with events enabled.
+pub fn parse_events(
+    response: &SuiTransactionBlockResponse,
+    package_id: ObjectID,
+) -> Result<Vec<DeepBookEvent>> {
+    let mut events = vec![];
+    if let Some(tx_events) = &response.events {
+        for event in &tx_events.data {
+            if let Some(parsed) = parse_event(event, package_id)? {
+                events.push(parsed);
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Net change of each manager's balance per coin type, in on-chain units, from the
+/// `BalanceEvent`s among `events`. Coins that moved in and back out again net to zero
+/// and are left out.
+pub fn manager_balance_changes<'a>(
+    events: impl IntoIterator<Item = &'a DeepBookEvent>,
+) -> BTreeMap<(ObjectID, String), i128> {
+    let mut changes = BTreeMap::new();
+    for event in events {
+        if let DeepBookEvent::Balance(balance) = event {
+            let amount = balance.amount as i128;
+            *changes
+                .entry((balance.balance_manager_id, balance.asset.clone()))
+                .or_insert(0) += if balance.deposit { amount } else { -amount };
+        }
+    }
+    changes.retain(|_, change| *change != 0);
+    changes
+}
+
+/// ID of the order a transaction placed for `client_order_id` on one side of a pool.
+/// Resting orders are found by their `OrderPlaced` event; an order that filled in full
+/// on placement never rests, so its ID is taken from the fills it took as a taker.
+pub fn placed_order_id(
+    response: &SuiTransactionBlockResponse,
+    package_id: ObjectID,
+    pool_id: ObjectID,
+    balance_manager_id: ObjectID,
+    client_order_id: u64,
+    is_bid: bool,
+) -> Result<Option<u128>> {
+    let mut taker_order_id = None;
+    for event in parse_events(response, package_id)? {
+        match event {
+            DeepBookEvent::OrderPlaced(placed)
+                if placed.pool_id == pool_id
+                    && placed.balance_manager_id == balance_manager_id
+                    && placed.client_order_id == client_order_id
+                    && placed.is_bid == is_bid =>
+            {
+                return Ok(Some(placed.order_id));
+            }
+            DeepBookEvent::OrderFilled(fill)
+                if fill.pool_id == pool_id
+                    && fill.taker_balance_manager_id == balance_manager_id
+                    && fill.taker_client_order_id == client_order_id
+                    && fill.taker_is_bid == is_bid =>
+            {
+                taker_order_id.get_or_insert(fill.taker_order_id);
+            }
+            _ => {}
+        }
+    }
+    Ok(taker_order_id)
+}
+
+impl TradingClient {
+    /// Stream every placement, fill, modification, cancellation and expiry of the
+    /// manager's orders on `pool_keys`, as they happen.
+    ///
+    /// Each pool gets its own event subscription and the streams are merged, so events
+    /// from different pools arrive in no particular order. Needs a `SuiClient` built
+    /// with a websocket URL. Subscription errors and undecodable events are yielded as
+    /// errors; the stream ends when every subscription closes.
+    pub async fn subscribe_manager_events(
+        &self,
+        manager_key: Option<&str>,
+        pool_keys: &[&str],
+    ) -> Result<impl Stream<Item = Result<ManagerEvent>>> {
+        let manager_id = self.manager_id(manager_key)?;
+        let package_id = self.config.package_id()?;
+        let client = self.ctx.client()?;
+
+        let mut subscriptions = Vec::with_capacity(pool_keys.len());
+        for pool_key in pool_keys {
+            let pool_id = self.config.pool(pool_key)?.id()?;
+            let filter = EventFilter::All(vec![
+                EventFilter::Package(package_id),
+                EventFilter::MoveEventField {
+                    path: "/pool_id".to_string(),
+                    value: serde_json::Value::String(pool_id.to_string()),
+                },
+            ]);
+            let events = client.event_api().subscribe_event(filter).await?;
+            let pool_key = pool_key.to_string();
+            subscriptions.push(Box::pin(events.filter_map(move |event| {
+                let pool_key = pool_key.clone();
+                async move {
+                    let event = match event {
+                        Ok(event) => parse_event(&event, package_id),
+                        Err(e) => Err(e.into()),
+                    };
+                    match event {
+                        Ok(Some(event)) if event.involves_manager(manager_id) => {
+                            Some(Ok(ManagerEvent { pool_key, event }))
+                        }
+                        Ok(_) => None,
+                        Err(e) => Some(Err(e)),
+                    }
+                }
+            })));
+        }
+        Ok(stream::select_all(subscriptions))
+    }
+
+    /// Quantity-weighted average price of the fills a transaction produced on a pool,
+    /// in human units. Pass `taker_order_id` to restrict it to one order's fills;
+    /// returns `None` when nothing filled.
+    pub fn average_fill_price(
+        &self,
+        pool_key: &str,
+        response: &SuiTransactionBlockResponse,
+        taker_order_id: Option<u128>,
+    ) -> Result<Option<f64>> {
+        let pool = self.config.pool(pool_key)?;
+        let pool_id = pool.id()?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let (mut base_filled, mut quote_filled) = (0u128, 0u128);
+        for event in parse_events(response, self.config.package_id()?)? {
+            if let DeepBookEvent::OrderFilled(fill) = event {
+                if fill.pool_id != pool_id
+                    || taker_order_id.is_some_and(|id| id != fill.taker_order_id)
+                {
+                    continue;
+                }
+                base_filled += fill.base_quantity as u128;
+                quote_filled += fill.quote_quantity as u128;
+            }
+        }
+
+        if base_filled == 0 {
+            return Ok(None);
+        }
+        // Total quote over total base weights each fill's price by its quantity.
+        let base_filled = base_filled as f64 / base.scalar as f64;
+        let quote_filled = quote_filled as f64 / quote.scalar as f64;
+        Ok(Some(quote_filled / base_filled))
+    }
+}
+
+fn decode<T: DeserializeOwned>(event: &SuiEvent) -> Result<T> {
+    Ok(serde_json::from_value(event.parsed_json.clone())?)
+}
+
+/// A Move `TypeName` is rendered as `{ "name": ... }`, its address without the `0x`.
+fn type_name<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct TypeName {
+        name: String,
+    }
+    let TypeName { name } = TypeName::deserialize(deserializer)?;
+    Ok(match name.strip_prefix("0x") {
+        Some(_) => name,
+        None => format!("0x{name}"),
+    })
+}
+
+/// Move integers wider than 32 bits are rendered as JSON strings.
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANAGER_ID: &str = "0x08933685e0246a2ddae2f5e5628fdeba09de831cadf5ad949db308807f18bee5";
+    const SUI: &str = "0000000000000000000000000000000000000000000000000000000000000002::sui::SUI";
+
+    fn balance_event(asset: &str, amount: u64, deposit: bool) -> DeepBookEvent {
+        DeepBookEvent::Balance(
+            serde_json::from_value(serde_json::json!({
+                "balance_manager_id": MANAGER_ID,
+                "asset": { "name": asset },
+                "amount": amount.to_string(),
+                "deposit": deposit,
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn balance_changes_net_deposits_against_withdrawals() {
+        let events = [
+            balance_event(SUI, 5_000, true),
+            balance_event(SUI, 1_500, false),
+            balance_event("0xdee9::deep::DEEP", 700, false),
+            balance_event("0xdee9::deep::DEEP", 700, true),
+        ];
+        let changes = manager_balance_changes(&events);
+
+        let manager_id = ObjectID::from_hex_literal(MANAGER_ID).unwrap();
+        assert_eq!(
+            changes.into_iter().collect::<Vec<_>>(),
+            vec![((manager_id, format!("0x{SUI}")), 3_500)]
+        );
+    }
+}
@@ -0,0 +1,797 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use futures::future::join_all;
+use shared_crypto::intent::Intent;
+use sui_config::{SUI_KEYSTORE_FILENAME, sui_config_dir};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore};
+use sui_sdk::{
+    rpc_types::{
+        ObjectChange, SuiExecutionStatus, SuiTransactionBlockEffectsAPI,
+        SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    },
+    types::{
+        base_types::ObjectRef,
+        crypto::ToFromBytes,
+        object::Owner,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        quorum_driver_types::ExecuteTransactionRequestType,
+        signature::GenericSignature,
+        transaction::{
+            Argument, CallArg, Command, ProgrammableTransaction, Transaction, TransactionData,
+            TransactionDataAPI,
+        },
+    },
+};
+
+use crate::builder::TransactionBuilder;
+use crate::client::TradingClient;
+use crate::config::Coin;
+use crate::error::ClientError;
+use crate::events::placed_order_id;
+use crate::types::{CreatedObjects, PlaceLimitOrderParams, PlacedQuote, SwapParams, SwapResult};
+
+/// Gas budget heuristic: a fixed base plus an increment per Move call, capped.
+pub const GAS_BUDGET_BASE: u64 = 10_000_000;
+pub const GAS_BUDGET_PER_MOVE_CALL: u64 = 4_000_000;
+pub const MAX_GAS_BUDGET: u64 = 500_000_000;
+
+/// Estimate a gas budget from the number of Move calls in the PTB, without a round trip.
+pub fn estimate_gas_budget(pt: &ProgrammableTransaction) -> u64 {
+    let move_calls = pt
+        .commands
+        .iter()
+        .filter(|command| matches!(command, Command::MoveCall(_)))
+        .count() as u64;
+    (GAS_BUDGET_BASE + GAS_BUDGET_PER_MOVE_CALL * move_calls).min(MAX_GAS_BUDGET)
+}
+
+impl TradingClient {
+    /// Sign a PTB with the sender's key and execute it, paying gas from the sender's coins.
+    /// Without an explicit `gas_budget` the budget comes from `estimate_gas_budget`.
+    pub async fn execute(
+        &self,
+        pt: ProgrammableTransaction,
+        gas_budget: Option<u64>,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let gas_budget = gas_budget.unwrap_or_else(|| estimate_gas_budget(&pt));
+        let tx_data = self
+            .ctx
+            .transaction_data(self.sender, pt, gas_budget)
+            .await?;
+        self.sign_and_execute(tx_data).await
+    }
+
+    /// Like `execute`, but returns the objects the transaction created, e.g. to pick up
+    /// the ID of a new balance manager or pool.
+    pub async fn execute_and_get_created(
+        &self,
+        pt: ProgrammableTransaction,
+        gas_budget: Option<u64>,
+    ) -> Result<CreatedObjects> {
+        let response = self.execute(pt, gas_budget).await?;
+        Ok(created_objects(&response))
+    }
+
+    /// Like `execute`, but pays gas with exactly the given coins. With a `gas_price` as
+    /// well, nothing is fetched before submission, so a caller tracking its own gas coin
+    /// versions (see `gas_object_ref`) saves the lookups on every transaction.
+    pub async fn execute_with_gas(
+        &self,
+        pt: ProgrammableTransaction,
+        gas_coins: Vec<ObjectRef>,
+        gas_price: Option<u64>,
+        gas_budget: Option<u64>,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let gas_budget = gas_budget.unwrap_or_else(|| estimate_gas_budget(&pt));
+        let gas_price = match gas_price {
+            Some(gas_price) => gas_price,
+            None => self.ctx.gas_price().await?,
+        };
+        let tx_data =
+            TransactionData::new_programmable(self.sender, gas_coins, pt, gas_budget, gas_price);
+        self.sign_and_execute(tx_data).await
+    }
+
+    /// Sign with the sender's key from the local keystore and submit. In a delegated
+    /// setup the sender is the trade cap holder, not the manager's owner.
+    pub async fn sign_and_execute(
+        &self,
+        tx_data: TransactionData,
+    ) -> Result<SuiTransactionBlockResponse> {
+        if self.preflight {
+            self.preflight_check(&tx_data).await?;
+        }
+        if tx_data.sender() != self.sender {
+            return Err(anyhow!(
+                "Transaction sender {} is not the client's signer {}",
+                tx_data.sender(),
+                self.sender
+            ));
+        }
+
+        let keystore = FileBasedKeystore::new(&sui_config_dir()?.join(SUI_KEYSTORE_FILENAME))?;
+        let signature = keystore.sign_secure(&self.sender, &tx_data, Intent::sui_transaction())?;
+        self.submit(tx_data, vec![signature.into()]).await
+    }
+
+    /// Build the transaction for a PTB without signing it, e.g. for a multisig or cold
+    /// signer. Pass it to `export_transaction` to get the bytes signers expect.
+    pub async fn build_transaction(
+        &self,
+        pt: ProgrammableTransaction,
+        gas_budget: Option<u64>,
+    ) -> Result<TransactionData> {
+        let gas_budget = gas_budget.unwrap_or_else(|| estimate_gas_budget(&pt));
+        self.ctx.transaction_data(self.sender, pt, gas_budget).await
+    }
+
+    /// Submit a transaction with signatures collected elsewhere, each given as the
+    /// base64 of a serialized Sui signature (including multisig).
+    pub async fn submit_signed(
+        &self,
+        tx_data: TransactionData,
+        signatures: &[String],
+    ) -> Result<SuiTransactionBlockResponse> {
+        if self.preflight {
+            self.preflight_check(&tx_data).await?;
+        }
+        let signatures = signatures
+            .iter()
+            .map(|signature| Ok(GenericSignature::from_bytes(&BASE64.decode(signature)?)?))
+            .collect::<Result<Vec<_>>>()?;
+        self.submit(tx_data, signatures).await
+    }
+
+    async fn submit(
+        &self,
+        tx_data: TransactionData,
+        signatures: Vec<GenericSignature>,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let client = self.ctx.client()?;
+        let response = self
+            .with_rpc_timeout(client.quorum_driver_api().execute_transaction_block(
+                Transaction::from_generic_sig_data(tx_data, signatures),
+                SuiTransactionBlockResponseOptions::full_content(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            ))
+            .await?;
+
+        if let Some(effects) = &response.effects {
+            if let SuiExecutionStatus::Failure { error } = effects.status() {
+                return Err(anyhow!("Transaction {} failed: {}", response.digest, error));
+            }
+        }
+        Ok(response)
+    }
+
+    /// Swap base for quote, send the output coins to the sender and report what it received.
+    pub async fn execute_swap_exact_base_for_quote(
+        &self,
+        params: &SwapParams,
+    ) -> Result<SwapResult> {
+        self.ensure_deep_reserve(params, true).await?;
+        let mut builder = TransactionBuilder::new(self);
+        builder.swap_exact_base_for_quote(params).await?;
+        builder.transfer_remaining(self.sender);
+        let response = self.execute(builder.finish()?, None).await?;
+        self.swap_result(&params.pool_key, &response)
+    }
+
+    /// Swap quote for base, send the output coins to the sender and report what it received.
+    pub async fn execute_swap_exact_quote_for_base(
+        &self,
+        params: &SwapParams,
+    ) -> Result<SwapResult> {
+        self.ensure_deep_reserve(params, false).await?;
+        let mut builder = TransactionBuilder::new(self);
+        builder.swap_exact_quote_for_base(params).await?;
+        builder.transfer_remaining(self.sender);
+        let response = self.execute(builder.finish()?, None).await?;
+        self.swap_result(&params.pool_key, &response)
+    }
+
+    /// A whitelisted pool pays swap fees from its own DEEP, and a short reserve aborts
+    /// the swap; fail before signing instead.
+    async fn ensure_deep_reserve(&self, params: &SwapParams, base_in: bool) -> Result<()> {
+        if self.whitelisted(&params.pool_key).await?
+            && !self.has_deep_reserve(params, base_in).await?
+        {
+            return Err(anyhow!(
+                "Whitelisted pool {} does not hold enough DEEP to cover this swap's fees",
+                params.pool_key
+            ));
+        }
+        Ok(())
+    }
+
+    /// Place and execute a two-sided quote, then read both order IDs from its events.
+    pub async fn execute_two_sided_quote(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        bid: &PlaceLimitOrderParams,
+        ask: &PlaceLimitOrderParams,
+    ) -> Result<PlacedQuote> {
+        let manager_key = self.manager_key(manager_key)?;
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        self.place_two_sided_quote(&mut ptb, pool_key, Some(manager_key), bid, ask)
+            .await?;
+        let response = self.execute(ptb.finish(), None).await?;
+
+        let pool_id = self.config.pool(pool_key)?.id()?;
+        let manager_id = self.balance_manager_id(manager_key)?;
+        let package_id = self.config.package_id()?;
+        Ok(PlacedQuote {
+            digest: response.digest.to_string(),
+            bid_order_id: placed_order_id(
+                &response,
+                package_id,
+                pool_id,
+                manager_id,
+                bid.client_order_id,
+                true,
+            )?,
+            ask_order_id: placed_order_id(
+                &response,
+                package_id,
+                pool_id,
+                manager_id,
+                ask.client_order_id,
+                false,
+            )?,
+        })
+    }
+
+    /// Decode a swap's outcome from the sender's balance changes. Gas is added back to
+    /// the SUI change so it does not count against a SUI leg of the swap.
+    pub fn swap_result(
+        &self,
+        pool_key: &str,
+        response: &SuiTransactionBlockResponse,
+    ) -> Result<SwapResult> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+        let balance_changes = response
+            .balance_changes
+            .as_ref()
+            .ok_or_else(|| anyhow!("Transaction {} has no balance changes", response.digest))?;
+        let gas_used = response
+            .effects
+            .as_ref()
+            .map(|effects| effects.gas_cost_summary().net_gas_usage())
+            .unwrap_or_default();
+
+        let net_change = |coin: &Coin| -> Result<f64> {
+            let coin_type = self.config.coin_type(&coin.type_)?;
+            let mut change: i128 = balance_changes
+                .iter()
+                .filter(|change| change.owner == Owner::AddressOwner(self.sender))
+                .filter(|change| change.coin_type.to_canonical_string(true) == coin_type)
+                .map(|change| change.amount)
+                .sum();
+            if coin.type_.ends_with("::sui::SUI") {
+                change += gas_used as i128;
+            }
+            Ok(change as f64 / coin.scalar as f64)
+        };
+
+        let deep_type = self.config.coin_type("DEEP")?;
+        let deep_is_pool_coin = self.config.coin_type(&pool.base_coin)? == deep_type
+            || self.config.coin_type(&pool.quote_coin)? == deep_type;
+        Ok(SwapResult {
+            base_received: net_change(base)?,
+            quote_received: net_change(quote)?,
+            deep_spent: if deep_is_pool_coin {
+                0.0
+            } else {
+                -net_change(deep)?
+            },
+        })
+    }
+
+    /// Place a limit order, retrying up to `max_attempts` times on timeouts without risk
+    /// of placing it twice.
+    ///
+    /// The transaction is built once and every attempt resubmits the same bytes, so it
+    /// has one digest and can execute at most once, and it pins the same gas coin
+    /// versions so no other transaction can take its place. After a failed submission
+    /// the digest is looked up first: if the original landed, its response is returned
+    /// instead of resubmitting.
+    pub async fn place_limit_order_idempotent(
+        &self,
+        params: &PlaceLimitOrderParams,
+        max_attempts: usize,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        self.place_limit_order(&mut ptb, params).await?;
+        let pt = ptb.finish();
+        let gas_budget = estimate_gas_budget(&pt);
+        let tx_data = self
+            .ctx
+            .transaction_data(self.sender, pt, gas_budget)
+            .await?;
+        let digest = tx_data.digest();
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let error = match self.sign_and_execute(tx_data.clone()).await {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            let client = self.ctx.client()?;
+            let landed = self
+                .with_rpc_timeout(client.read_api().get_transaction_with_options(
+                    digest,
+                    SuiTransactionBlockResponseOptions::full_content(),
+                ))
+                .await;
+            if let Ok(response) = landed {
+                if let Some(effects) = &response.effects {
+                    if let SuiExecutionStatus::Failure { error } = effects.status() {
+                        return Err(anyhow!("Transaction {} failed: {}", digest, error));
+                    }
+                }
+                return Ok(response);
+            }
+
+            let timed_out = matches!(
+                error.downcast_ref::<ClientError>(),
+                Some(ClientError::Timeout(_))
+            );
+            if !timed_out || attempt >= max_attempts {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Place any number of limit orders, splitting them across transactions that stay
+    /// within the client's PTB limits. Each transaction generates one proof per manager.
+    pub async fn place_limit_orders_batched(
+        &self,
+        orders: &[PlaceLimitOrderParams],
+    ) -> Result<Vec<BatchResult>> {
+        self.execute_in_batches(orders.len(), async |ptb, proofs, index| {
+            let order = &orders[index];
+            let manager_key = self.manager_key(order.balance_manager_key.as_deref())?;
+            let proof = match proofs.get(manager_key) {
+                Some(proof) => *proof,
+                None => {
+                    let proof = self.generate_proof(ptb, Some(manager_key)).await?;
+                    proofs.insert(manager_key.to_string(), proof);
+                    proof
+                }
+            };
+            self.place_limit_order_with_proof(ptb, order, proof).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Cancel any number of the manager's orders on a pool, splitting them across
+    /// transactions that stay within the client's PTB limits.
+    pub async fn cancel_orders_batched(
+        &self,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        order_ids: &[u128],
+    ) -> Result<Vec<BatchResult>> {
+        let manager_key = self.manager_key(manager_key)?;
+        self.execute_in_batches(order_ids.len(), async |ptb, proofs, index| {
+            let proof = match proofs.get(manager_key) {
+                Some(proof) => *proof,
+                None => {
+                    let proof = self.generate_proof(ptb, Some(manager_key)).await?;
+                    proofs.insert(manager_key.to_string(), proof);
+                    proof
+                }
+            };
+            self.cancel_order_with_proof(ptb, pool_key, Some(manager_key), order_ids[index], proof)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Execute `count` operations in sequence, as many per transaction as fit.
+    ///
+    /// `build` adds one operation to the PTB of its batch, along with any proof it needs
+    /// that the batch has not generated yet. An operation that fails to build is left
+    /// out of its transaction and reported in the batch's `build_errors`; a transaction
+    /// that fails to execute is reported in its `result`. The remaining batches still run.
+    async fn execute_in_batches<F>(&self, count: usize, build: F) -> Result<Vec<BatchResult>>
+    where
+        F: AsyncFn(&mut ProgrammableTransactionBuilder, &mut BatchProofs, usize) -> Result<()>,
+    {
+        let gas = GasPayment {
+            coins: self.ctx.gas_coins(self.sender).await?,
+            price: self.ctx.gas_price().await?,
+        };
+        let mut results = vec![];
+        let mut start = 0;
+        while start < count {
+            let batch = self.build_batch(start, count, &gas, &build).await?;
+            let result = match batch.pt {
+                Some(pt) => self.execute(pt, None).await,
+                None => Err(anyhow!(
+                    "None of operations {:?} could be built",
+                    start..batch.end
+                )),
+            };
+            results.push(BatchResult {
+                operations: start..batch.end,
+                result,
+                build_errors: batch.build_errors,
+            });
+            start = batch.end;
+        }
+        Ok(results)
+    }
+
+    /// Build the batch of operations starting at `start`. Operations are added one at a
+    /// time and the batch ends before the first one that would take the transaction past
+    /// either limit; that operation is the only one built twice, as it opens the next
+    /// batch. A single operation is kept even if it alone exceeds a limit.
+    async fn build_batch<F>(
+        &self,
+        start: usize,
+        count: usize,
+        gas: &GasPayment,
+        build: &F,
+    ) -> Result<Batch>
+    where
+        F: AsyncFn(&mut ProgrammableTransactionBuilder, &mut BatchProofs, usize) -> Result<()>,
+    {
+        let mut built: Option<ProgrammableTransaction> = None;
+        let mut proofs = BatchProofs::new();
+        let mut build_errors = vec![];
+        let mut end = start;
+        while end < count {
+            let mut ptb = match &built {
+                Some(pt) => resume(pt)?,
+                None => ProgrammableTransactionBuilder::new(),
+            };
+            let mut probe_proofs = proofs.clone();
+            if let Err(error) = build(&mut ptb, &mut probe_proofs, end).await {
+                build_errors.push((end, error));
+                end += 1;
+                continue;
+            }
+            let probe = ptb.finish();
+            let fits = probe.commands.len() <= self.max_commands_per_ptb
+                && self.transaction_size(probe.clone(), gas)? <= self.max_tx_bytes;
+            if !fits && built.is_some() {
+                break;
+            }
+            built = Some(probe);
+            proofs = probe_proofs;
+            end += 1;
+            if !fits {
+                break;
+            }
+        }
+        Ok(Batch {
+            end,
+            pt: built,
+            build_errors,
+        })
+    }
+
+    /// Serialized size of the `TransactionData` that `pt` would be submitted as.
+    fn transaction_size(&self, pt: ProgrammableTransaction, gas: &GasPayment) -> Result<usize> {
+        let gas_budget = estimate_gas_budget(&pt);
+        let tx_data = TransactionData::new_programmable(
+            self.sender,
+            gas.coins.clone(),
+            pt,
+            gas_budget,
+            gas.price,
+        );
+        Ok(bcs::to_bytes(&tx_data)?.len())
+    }
+
+    /// Dev-inspect a transaction and fail if it would abort, without spending gas.
+    pub async fn preflight_check(&self, tx_data: &TransactionData) -> Result<()> {
+        let results = self.dev_inspect_kind(tx_data.kind().clone()).await?;
+        if let SuiExecutionStatus::Failure { error } = results.effects.status() {
+            return Err(anyhow!(
+                "Preflight failed, transaction would abort: {}",
+                error
+            ));
+        }
+        Ok(())
+    }
+
+    /// Place each manager's orders in its own transaction, submitting all of them
+    /// concurrently. Every transaction gets its own TradeProof and gas coin, so the
+    /// sender must own at least one SUI coin per manager.
+    ///
+    /// Results are reported per manager; one manager failing does not affect the others.
+    pub async fn place_orders_for_managers(
+        &self,
+        orders: HashMap<String, Vec<PlaceLimitOrderParams>>,
+    ) -> Result<HashMap<String, Result<SuiTransactionBlockResponse>>> {
+        let gas_coins = self.ctx.gas_coins(self.sender).await?;
+        if gas_coins.len() < orders.len() {
+            return Err(anyhow!(
+                "Placing orders for {} managers concurrently needs {} gas coins, but the sender owns {}",
+                orders.len(),
+                orders.len(),
+                gas_coins.len()
+            ));
+        }
+
+        let submissions =
+            orders
+                .into_iter()
+                .zip(gas_coins)
+                .map(|((manager_key, params), gas_coin)| async move {
+                    let result = self
+                        .place_manager_orders(&manager_key, &params, gas_coin)
+                        .await;
+                    (manager_key, result)
+                });
+        Ok(join_all(submissions).await.into_iter().collect())
+    }
+
+    async fn place_manager_orders(
+        &self,
+        manager_key: &str,
+        params: &[PlaceLimitOrderParams],
+        gas_coin: ObjectRef,
+    ) -> Result<SuiTransactionBlockResponse> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let proof = self.generate_proof(&mut ptb, Some(manager_key)).await?;
+        for order in params {
+            let order = PlaceLimitOrderParams {
+                balance_manager_key: Some(manager_key.to_string()),
+                ..order.clone()
+            };
+            self.place_limit_order_with_proof(&mut ptb, &order, proof)
+                .await?;
+        }
+        self.execute_with_gas(ptb.finish(), vec![gas_coin], None, None)
+            .await
+    }
+}
+
+/// One transaction of a batched call, with the indices of the operations it carried.
+#[derive(Debug)]
+pub struct BatchResult {
+    /// Operations the transaction was built from, including any in `build_errors`.
+    pub operations: Range<usize>,
+    pub result: Result<SuiTransactionBlockResponse>,
+    /// Operations that failed to build and were left out of the transaction, by index.
+    pub build_errors: Vec<(usize, anyhow::Error)>,
+}
+
+/// Proofs a batch's transaction has generated so far, by manager key.
+type BatchProofs = HashMap<String, Argument>;
+
+/// Gas coins and price a batched call's transactions are sized with.
+struct GasPayment {
+    coins: Vec<ObjectRef>,
+    price: u64,
+}
+
+/// A batch as `build_batch` assembled it; `pt` is `None` if no operation built.
+struct Batch {
+    end: usize,
+    pt: Option<ProgrammableTransaction>,
+    build_errors: Vec<(usize, anyhow::Error)>,
+}
+
+/// A builder holding the inputs and commands of `pt`, at the same indices, so that
+/// more commands can be added to a finished PTB.
+fn resume(pt: &ProgrammableTransaction) -> Result<ProgrammableTransactionBuilder> {
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let mut pure_inputs = HashSet::new();
+    for (index, input) in pt.inputs.iter().enumerate() {
+        let argument = match input {
+            // A finished PTB only repeats a pure value that was forced to stay separate.
+            CallArg::Pure(bytes) => {
+                let separate = !pure_inputs.insert(bytes);
+                ptb.pure_bytes(bytes.clone(), separate)
+            }
+            other => ptb.input(other.clone())?,
+        };
+        if argument != Argument::Input(index as u16) {
+            return Err(anyhow!(
+                "Input {} moved to {:?} when resuming the PTB",
+                index,
+                argument
+            ));
+        }
+    }
+    for command in &pt.commands {
+        ptb.command(command.clone());
+    }
+    Ok(ptb)
+}
+
+/// Objects created by an executed transaction, from its object changes.
+pub fn created_objects(response: &SuiTransactionBlockResponse) -> CreatedObjects {
+    let objects = response
+        .object_changes
+        .iter()
+        .flatten()
+        .filter_map(|change| match change {
+            ObjectChange::Created {
+                object_id,
+                object_type,
+                ..
+            } => Some((*object_id, object_type.to_string())),
+            _ => None,
+        })
+        .collect();
+    CreatedObjects {
+        digest: response.digest.to_string(),
+        objects,
+    }
+}
+
+/// The gas coin's reference after a transaction, for reuse as the next one's gas.
+pub fn gas_object_ref(response: &SuiTransactionBlockResponse) -> Option<ObjectRef> {
+    response
+        .effects
+        .as_ref()
+        .map(|effects| effects.gas_object().reference.to_object_ref())
+}
+
+/// Serialize a transaction to base64 BCS, the tx-bytes format Sui wallets and
+/// `sui keytool sign` take.
+pub fn export_transaction(tx_data: &TransactionData) -> Result<String> {
+    Ok(BASE64.encode(bcs::to_bytes(tx_data)?))
+}
+
+/// Parse tx bytes produced by `export_transaction`.
+pub fn import_transaction(tx_bytes: &str) -> Result<TransactionData> {
+    Ok(bcs::from_bytes(&BASE64.decode(tx_bytes)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use sui_sdk::types::base_types::{ObjectDigest, ObjectID, SequenceNumber};
+
+    use super::*;
+    use crate::client::test_support::offline_client;
+    use crate::context::BuildContext;
+
+    fn client(max_commands: usize, max_tx_bytes: usize) -> TradingClient {
+        let gas_coin = (
+            ObjectID::from_single_byte(0x6a),
+            SequenceNumber::from_u64(1),
+            ObjectDigest::MIN,
+        );
+        offline_client(
+            BuildContext::fixed()
+                .with_gas_price(1_000)
+                .with_gas_coins(vec![gas_coin]),
+        )
+        .with_ptb_limits(max_commands, max_tx_bytes)
+    }
+
+    async fn gas(client: &TradingClient) -> GasPayment {
+        GasPayment {
+            coins: client.ctx.gas_coins(client.sender).await.unwrap(),
+            price: client.ctx.gas_price().await.unwrap(),
+        }
+    }
+
+    /// An operation of `commands` gas-coin splits.
+    fn split_op(ptb: &mut ProgrammableTransactionBuilder, commands: usize) -> Result<()> {
+        for _ in 0..commands {
+            let amount = ptb.pure(1u64)?;
+            ptb.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+        }
+        Ok(())
+    }
+
+    /// Build every batch `execute_in_batches` would submit.
+    async fn batches<F>(
+        client: &TradingClient,
+        count: usize,
+        build: F,
+    ) -> Vec<(Range<usize>, Batch)>
+    where
+        F: AsyncFn(&mut ProgrammableTransactionBuilder, &mut BatchProofs, usize) -> Result<()>,
+    {
+        let gas = gas(client).await;
+        let mut batches = vec![];
+        let mut start = 0;
+        while start < count {
+            let batch = client
+                .build_batch(start, count, &gas, &build)
+                .await
+                .unwrap();
+            let end = batch.end;
+            batches.push((start..end, batch));
+            start = end;
+        }
+        batches
+    }
+
+    fn ranges(batches: &[(Range<usize>, Batch)]) -> Vec<Range<usize>> {
+        batches.iter().map(|(range, _)| range.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn batches_fill_up_to_the_command_limit() {
+        let client = client(4, usize::MAX);
+        let built = batches(&client, 7, async |ptb, _, _| split_op(ptb, 1)).await;
+        assert_eq!(ranges(&built), vec![0..4, 4..7]);
+    }
+
+    #[tokio::test]
+    async fn an_operation_over_the_limit_goes_alone() {
+        let client = client(3, usize::MAX);
+        let built = batches(&client, 3, async |ptb, _, index| {
+            split_op(ptb, if index == 1 { 5 } else { 2 })
+        })
+        .await;
+        assert_eq!(ranges(&built), vec![0..1, 1..2, 2..3]);
+        assert_eq!(built[1].1.pt.as_ref().unwrap().commands.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn byte_limit_splits_batches() {
+        let unlimited = client(1024, usize::MAX);
+        let gas = gas(&unlimited).await;
+        let two_ops = {
+            let mut ptb = ProgrammableTransactionBuilder::new();
+            split_op(&mut ptb, 2).unwrap();
+            unlimited.transaction_size(ptb.finish(), &gas).unwrap()
+        };
+        let client = client(1024, two_ops);
+        let built = batches(&client, 5, async |ptb, _, _| split_op(ptb, 1)).await;
+        assert_eq!(ranges(&built), vec![0..2, 2..4, 4..5]);
+    }
+
+    #[tokio::test]
+    async fn operations_are_built_once_and_share_the_batch_proof() {
+        let client = client(3, usize::MAX);
+        let builds = Cell::new(0);
+        let built = batches(&client, 4, async |ptb, proofs, _| {
+            builds.set(builds.get() + 1);
+            if !proofs.contains_key("MANAGER") {
+                // Stands in for the proof, which also takes a command.
+                let proof = ptb.pure(0u64)?;
+                let proof = ptb.command(Command::SplitCoins(Argument::GasCoin, vec![proof]));
+                proofs.insert("MANAGER".to_string(), proof);
+            }
+            split_op(ptb, 1)
+        })
+        .await;
+        assert_eq!(ranges(&built), vec![0..2, 2..4]);
+        // Only operation 2, which overflowed the first batch, is built a second time.
+        assert_eq!(builds.get(), 5);
+        for (_, batch) in &built {
+            assert_eq!(batch.pt.as_ref().unwrap().commands.len(), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_failing_operation_is_reported_and_left_out() {
+        let client = client(1024, usize::MAX);
+        let built = batches(&client, 4, async |ptb, _, index| {
+            if index == 2 {
+                return Err(anyhow!("cannot build {}", index));
+            }
+            split_op(ptb, 1)
+        })
+        .await;
+        assert_eq!(ranges(&built), vec![0..4]);
+        let batch = &built[0].1;
+        assert_eq!(batch.pt.as_ref().unwrap().commands.len(), 3);
+        assert_eq!(batch.build_errors.len(), 1);
+        assert_eq!(batch.build_errors[0].0, 2);
+    }
+}
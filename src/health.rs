@@ -0,0 +1,420 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use sui_sdk::{
+    rpc_types::{SuiObjectDataOptions, SuiParsedData},
+    types::{
+        TypeTag,
+        base_types::{ObjectID, SuiAddress},
+        dynamic_field::DynamicFieldName,
+        object::Owner,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+    },
+};
+
+use crate::client::TradingClient;
+use crate::queries::return_value;
+use crate::transactions::move_call;
+
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Outcome of every probe run by `health_check`, including the ones that failed.
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+
+    fn record(&mut self, name: impl Into<String>, result: Result<String, String>) {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.checks.push(HealthCheck {
+            name: name.into(),
+            passed,
+            detail,
+        });
+    }
+}
+
+/// How the configured DeepBook package compares to what the registry accepts.
+#[derive(Debug, Clone)]
+pub struct PackageStatus {
+    pub package_id: ObjectID,
+    /// Version reported by the configured package's `constants::current_version`.
+    pub package_version: u64,
+    /// Versions the registry currently allows pools to be called through.
+    pub allowed_versions: Vec<u64>,
+}
+
+impl PackageStatus {
+    /// Calls through the configured package are still accepted.
+    pub fn is_allowed(&self) -> bool {
+        self.allowed_versions.contains(&self.package_version)
+    }
+
+    /// The configured package is the newest allowed version. When this is false but
+    /// `is_allowed` holds, an upgrade has shipped and the package IDs should be updated
+    /// before the old version is disabled.
+    pub fn is_latest(&self) -> bool {
+        self.allowed_versions
+            .iter()
+            .all(|version| *version <= self.package_version)
+    }
+}
+
+/// On-chain state of a cap configured for a balance manager.
+#[derive(Debug, Clone)]
+pub struct CapCheck {
+    pub id: ObjectID,
+    pub exists: bool,
+    /// The cap's `balance_manager_id` points at the manager being verified.
+    pub belongs_to_manager: bool,
+    /// The cap is still in the manager's allow list, i.e. has not been revoked.
+    pub allow_listed: bool,
+    /// The client's sender owns the cap, so its transactions can pass it in.
+    pub held_by_sender: bool,
+}
+
+impl CapCheck {
+    pub fn is_valid(&self) -> bool {
+        self.exists && self.belongs_to_manager && self.allow_listed && self.held_by_sender
+    }
+}
+
+/// Result of `verify_manager`. Caps that are not configured are `None`.
+#[derive(Debug, Clone)]
+pub struct ManagerVerification {
+    pub manager_id: ObjectID,
+    pub owner: String,
+    pub owner_matches: bool,
+    pub trade_cap: Option<CapCheck>,
+    pub deposit_cap: Option<CapCheck>,
+    pub withdraw_cap: Option<CapCheck>,
+}
+
+impl ManagerVerification {
+    /// Whether the sender can trade on the manager: as its owner, or as the holder of
+    /// a valid trade cap in a delegated setup.
+    pub fn is_valid(&self) -> bool {
+        (self.owner_matches || self.trade_cap.as_ref().is_some_and(CapCheck::is_valid))
+            && [&self.trade_cap, &self.deposit_cap, &self.withdraw_cap]
+                .into_iter()
+                .flatten()
+                .all(CapCheck::is_valid)
+    }
+}
+
+impl TradingClient {
+    /// Check a configured manager against the chain: that it is a BalanceManager owned
+    /// by the sender, or traded through a trade cap the sender holds, and that each
+    /// configured cap exists, was minted for this manager, has not been revoked and is
+    /// held by the sender.
+    pub async fn verify_manager(
+        &self,
+        manager_key: Option<&str>,
+    ) -> anyhow::Result<ManagerVerification> {
+        let manager_key = self.manager_key(manager_key)?;
+        let manager = self.balance_manager(manager_key)?;
+        let manager_id = self.balance_manager_id(manager_key)?;
+
+        let (type_, fields) = self.object_fields(manager_id).await?;
+        if !type_.ends_with("::balance_manager::BalanceManager") {
+            return Err(anyhow!(
+                "Object {} is a {}, not a BalanceManager",
+                manager_id,
+                type_
+            ));
+        }
+        let owner = fields["owner"].as_str().unwrap_or_default().to_string();
+        let allow_listed = vec_set_contents(&fields["allow_listed"]);
+
+        Ok(ManagerVerification {
+            manager_id,
+            owner_matches: owner == self.sender.to_string(),
+            owner,
+            trade_cap: self
+                .check_cap(
+                    manager.trade_cap.as_deref(),
+                    "TradeCap",
+                    manager_id,
+                    &allow_listed,
+                )
+                .await?,
+            deposit_cap: self
+                .check_cap(
+                    manager.deposit_cap.as_deref(),
+                    "DepositCap",
+                    manager_id,
+                    &allow_listed,
+                )
+                .await?,
+            withdraw_cap: self
+                .check_cap(
+                    manager.withdraw_cap.as_deref(),
+                    "WithdrawCap",
+                    manager_id,
+                    &allow_listed,
+                )
+                .await?,
+        })
+    }
+
+    async fn check_cap(
+        &self,
+        cap: Option<&str>,
+        cap_type: &str,
+        manager_id: ObjectID,
+        allow_listed: &[String],
+    ) -> anyhow::Result<Option<CapCheck>> {
+        let Some(cap) = cap else {
+            return Ok(None);
+        };
+        let id = ObjectID::from_hex_literal(cap)?;
+        let Some((type_, fields)) = self.try_object_fields(id).await? else {
+            return Ok(Some(CapCheck {
+                id,
+                exists: false,
+                belongs_to_manager: false,
+                allow_listed: false,
+                held_by_sender: false,
+            }));
+        };
+        let cap_manager = fields["balance_manager_id"].as_str().unwrap_or_default();
+        Ok(Some(CapCheck {
+            id,
+            exists: true,
+            belongs_to_manager: type_.ends_with(&format!("::balance_manager::{}", cap_type))
+                && cap_manager == manager_id.to_string(),
+            allow_listed: allow_listed.contains(&id.to_string()),
+            held_by_sender: self.object_owner(id).await? == Some(Owner::AddressOwner(self.sender)),
+        }))
+    }
+
+    async fn object_owner(&self, id: ObjectID) -> anyhow::Result<Option<Owner>> {
+        let response = self
+            .ctx
+            .client()?
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+            .await?;
+        Ok(response.data.and_then(|data| data.owner))
+    }
+
+    /// Fields of the registry's current `RegistryInner`.
+    pub(crate) async fn registry_inner(&self) -> anyhow::Result<Value> {
+        // The registry keeps its state in a `Versioned` wrapper, as a dynamic field keyed
+        // by the inner struct's version.
+        let registry_id = self.config.registry_id()?;
+        let (_, fields) = self.object_fields(registry_id).await?;
+        let inner = &fields["inner"];
+        let inner = inner.get("fields").unwrap_or(inner);
+        let versioned_id = inner
+            .pointer("/id/id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Registry {} has no versioned inner object", registry_id))?;
+        let inner_version = json_u64(&inner["version"])
+            .ok_or_else(|| anyhow!("Registry {} has no inner version", registry_id))?;
+
+        let response = self
+            .ctx
+            .client()?
+            .read_api()
+            .get_dynamic_field_object(
+                ObjectID::from_hex_literal(versioned_id)?,
+                DynamicFieldName {
+                    type_: TypeTag::U64,
+                    value: Value::String(inner_version.to_string()),
+                },
+            )
+            .await?;
+        let registry_inner = match response.data.and_then(|data| data.content) {
+            Some(SuiParsedData::MoveObject(object)) => object.fields.to_json_value(),
+            _ => return Err(anyhow!("Registry {} inner state not found", registry_id)),
+        };
+        let value = &registry_inner["value"];
+        Ok(value.get("fields").unwrap_or(value).clone())
+    }
+
+    /// Compare the configured package's version with the versions the registry allows.
+    pub async fn check_package_version(&self) -> anyhow::Result<PackageStatus> {
+        let package_id = self.config.package_id()?;
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        move_call(
+            &mut ptb,
+            package_id,
+            "constants",
+            "current_version",
+            vec![],
+            vec![],
+        )?;
+        let results = self.dev_inspect(ptb).await?;
+        let package_version: u64 = return_value(&results, 0, 0)?;
+
+        let registry_inner = self.registry_inner().await?;
+        let allowed_versions = vec_set_contents(&registry_inner["allowed_versions"])
+            .iter()
+            .filter_map(|version| version.parse().ok())
+            .collect();
+
+        Ok(PackageStatus {
+            package_id,
+            package_version,
+            allowed_versions,
+        })
+    }
+
+    /// Probe the RPC node, the DeepBook package and every configured balance manager.
+    pub async fn health_check(&self) -> anyhow::Result<HealthReport> {
+        let mut report = HealthReport::default();
+
+        let rpc = match self.ctx.client() {
+            Ok(client) => client
+                .read_api()
+                .get_reference_gas_price()
+                .await
+                .map(|gas_price| format!("reference gas price {}", gas_price))
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        report.record("rpc", rpc);
+
+        let package = self.check_package().await;
+        report.record("deepbook_package", package);
+
+        let version = match self.check_package_version().await {
+            Ok(status) if !status.is_allowed() => Err(format!(
+                "package version {} is no longer allowed by the registry (allowed: {:?})",
+                status.package_version, status.allowed_versions
+            )),
+            Ok(status) if !status.is_latest() => Err(format!(
+                "package version {} is outdated (allowed: {:?}); update the package IDs",
+                status.package_version, status.allowed_versions
+            )),
+            Ok(status) => Ok(format!(
+                "package version {} is current",
+                status.package_version
+            )),
+            Err(e) => Err(e.to_string()),
+        };
+        report.record("deepbook_package_version", version);
+
+        let mut manager_keys: Vec<&String> = self.balance_managers.keys().collect();
+        manager_keys.sort();
+        for manager_key in manager_keys {
+            let result = self.check_manager(manager_key, self.sender).await;
+            report.record(format!("balance_manager:{}", manager_key), result);
+        }
+
+        Ok(report)
+    }
+
+    async fn check_package(&self) -> Result<String, String> {
+        let package_id = self.config.package_id().map_err(|e| e.to_string())?;
+        let client = self.ctx.client().map_err(|e| e.to_string())?;
+        let response = client
+            .read_api()
+            .get_object_with_options(package_id, SuiObjectDataOptions::new())
+            .await
+            .map_err(|e| e.to_string())?;
+        match response.data {
+            Some(_) => Ok(format!("package {} found", package_id)),
+            None => Err(format!("package {} not found", package_id)),
+        }
+    }
+
+    async fn check_manager(
+        &self,
+        manager_key: &str,
+        expected_owner: SuiAddress,
+    ) -> Result<String, String> {
+        let id = self
+            .balance_manager_id(manager_key)
+            .map_err(|e| e.to_string())?;
+        let (type_, fields) = self.object_fields(id).await.map_err(|e| e.to_string())?;
+        if !type_.ends_with("::balance_manager::BalanceManager") {
+            return Err(format!(
+                "object {} is a {}, not a BalanceManager",
+                id, type_
+            ));
+        }
+        let owner = fields["owner"].as_str().unwrap_or_default();
+        if owner == expected_owner.to_string() {
+            return Ok(format!("manager {} owned by {}", id, owner));
+        }
+
+        // A delegated trader is not the owner but holds a trade cap for the manager.
+        let manager = self
+            .balance_manager(manager_key)
+            .map_err(|e| e.to_string())?;
+        let trade_cap = self
+            .check_cap(
+                manager.trade_cap.as_deref(),
+                "TradeCap",
+                id,
+                &vec_set_contents(&fields["allow_listed"]),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        match trade_cap {
+            Some(cap) if cap.is_valid() => Ok(format!(
+                "manager {} owned by {}, traded by {} through cap {}",
+                id, owner, expected_owner, cap.id
+            )),
+            Some(cap) => Err(format!(
+                "manager {} is owned by {} and trade cap {} is not usable by {}: {:?}",
+                id, owner, cap.id, expected_owner, cap
+            )),
+            None => Err(format!(
+                "manager {} is owned by {}, expected {}",
+                id, owner, expected_owner
+            )),
+        }
+    }
+}
+
+/// The entries of a `VecSet` field as strings, whether or not the JSON keeps its
+/// struct wrapper. `TypeName` entries are reduced to their name.
+pub(crate) fn vec_set_contents(value: &Value) -> Vec<String> {
+    let contents = value
+        .get("contents")
+        .or_else(|| value.pointer("/fields/contents"));
+    contents
+        .and_then(Value::as_array)
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|entry| match entry {
+                    Value::String(entry) => Some(entry.clone()),
+                    Value::Number(entry) => Some(entry.to_string()),
+                    Value::Object(_) => entry
+                        .pointer("/fields/name")
+                        .or_else(|| entry.get("name"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A u64 field, which the RPC may render as a string or a number.
+fn json_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(value) => value.parse().ok(),
+        value => value.as_u64(),
+    }
+}
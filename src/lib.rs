@@ -0,0 +1,18 @@
+pub mod account;
+pub mod book;
+pub mod builder;
+pub mod candles;
+pub mod client;
+pub mod config;
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod execute;
+pub mod health;
+pub mod portfolio;
+pub mod protocol;
+pub mod queries;
+pub mod transactions;
+pub mod types;
+pub mod validation;
+pub mod watch;
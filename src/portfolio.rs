@@ -0,0 +1,103 @@
+use anyhow::{Result, anyhow};
+
+use crate::client::TradingClient;
+
+/// Coins tried, in order, to price an asset that has no pool against the target quote.
+pub const INTERMEDIATE_COINS: &[&str] = &["SUI", "USDC"];
+
+/// How one asset was valued by `get_notional_value`.
+#[derive(Debug, Clone)]
+pub struct AssetValuation {
+    pub coin_key: String,
+    pub balance: f64,
+    /// Price of one unit of the asset in the target quote coin.
+    pub price: f64,
+    /// Pools whose mid prices were multiplied to get `price`; empty when the asset is
+    /// the quote coin itself.
+    pub route: Vec<String>,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotionalValue {
+    pub quote_coin: String,
+    pub total: f64,
+    pub assets: Vec<AssetValuation>,
+}
+
+impl TradingClient {
+    /// Value the manager's balances of `coin_keys` in `quote_in`, pricing each asset at
+    /// the mid of a pool against `quote_in`, or through one of `INTERMEDIATE_COINS` when
+    /// no direct pool exists. Fails if an asset with a non-zero balance cannot be priced.
+    pub async fn get_notional_value(
+        &self,
+        manager_key: Option<&str>,
+        coin_keys: &[&str],
+        quote_in: &str,
+    ) -> Result<NotionalValue> {
+        let manager_id = self.manager_id(manager_key)?;
+        let mut assets = Vec::with_capacity(coin_keys.len());
+        for coin_key in coin_keys {
+            let balance = self.get_manager_balance_by_id(manager_id, coin_key).await?;
+            let (price, route) = if balance == 0.0 {
+                (0.0, vec![])
+            } else {
+                self.price_in(coin_key, quote_in).await?
+            };
+            assets.push(AssetValuation {
+                coin_key: coin_key.to_string(),
+                balance,
+                price,
+                route,
+                value: balance * price,
+            });
+        }
+
+        Ok(NotionalValue {
+            quote_coin: quote_in.to_string(),
+            total: assets.iter().map(|asset| asset.value).sum(),
+            assets,
+        })
+    }
+
+    /// Price of `coin_key` in `quote_in` and the pools it was derived from.
+    async fn price_in(&self, coin_key: &str, quote_in: &str) -> Result<(f64, Vec<String>)> {
+        if self.config.coin_type(coin_key)? == self.config.coin_type(quote_in)? {
+            return Ok((1.0, vec![]));
+        }
+        if let Some((price, pool_key)) = self.pair_price(coin_key, quote_in).await? {
+            return Ok((price, vec![pool_key]));
+        }
+        for intermediate in INTERMEDIATE_COINS {
+            if self.config.coin(intermediate).is_err() {
+                continue;
+            }
+            let Some((first, first_pool)) = self.pair_price(coin_key, intermediate).await? else {
+                continue;
+            };
+            let Some((second, second_pool)) = self.pair_price(intermediate, quote_in).await? else {
+                continue;
+            };
+            return Ok((first * second, vec![first_pool, second_pool]));
+        }
+        Err(anyhow!("No pool route prices {} in {}", coin_key, quote_in))
+    }
+
+    /// Mid price of `coin_key` in `quote_in` on a pool trading the two directly,
+    /// inverted when `coin_key` is that pool's quote. `None` without a usable pool.
+    async fn pair_price(&self, coin_key: &str, quote_in: &str) -> Result<Option<(f64, String)>> {
+        let Some(pool_key) = self.find_pool(coin_key, quote_in)? else {
+            return Ok(None);
+        };
+        let Some(mid) = self.get_mid_price(&pool_key).await? else {
+            return Ok(None);
+        };
+        let pool = self.config.pool(&pool_key)?;
+        let price = if self.config.coin_type(&pool.base_coin)? == self.config.coin_type(coin_key)? {
+            mid
+        } else {
+            1.0 / mid
+        };
+        Ok(Some((price, pool_key)))
+    }
+}
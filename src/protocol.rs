@@ -0,0 +1,213 @@
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use sui_sdk::types::{TypeTag, programmable_transaction_builder::ProgrammableTransactionBuilder};
+
+use crate::client::TradingClient;
+use crate::config::{FLOAT_SCALAR, price_to_units};
+use crate::health::vec_set_contents;
+use crate::queries::return_value;
+use crate::transactions::move_call;
+
+/// Smallest lot size `pool::create_pool` accepts, in base units.
+pub const MIN_LOT_SIZE: u64 = 1_000;
+
+/// Fee rates a governance proposal may set, scaled by `FLOAT_SCALAR`. Mirrors the
+/// bounds hard-coded in DeepBook's `governance` module, which has no getter for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeBounds {
+    pub min_taker_fee: u64,
+    pub max_taker_fee: u64,
+    pub min_maker_fee: u64,
+    pub max_maker_fee: u64,
+}
+
+pub const STABLE_FEE_BOUNDS: FeeBounds = FeeBounds {
+    min_taker_fee: 10_000,
+    max_taker_fee: 100_000,
+    min_maker_fee: 0,
+    max_maker_fee: 50_000,
+};
+
+pub const VOLATILE_FEE_BOUNDS: FeeBounds = FeeBounds {
+    min_taker_fee: 100_000,
+    max_taker_fee: 1_000_000,
+    min_maker_fee: 0,
+    max_maker_fee: 500_000,
+};
+
+/// Fee bounds governance applies to a stable or volatile pool.
+pub fn fee_bounds(stable: bool) -> FeeBounds {
+    if stable {
+        STABLE_FEE_BOUNDS
+    } else {
+        VOLATILE_FEE_BOUNDS
+    }
+}
+
+/// Protocol-wide values read from the chain. Fee bounds and size rules are Move
+/// constants with no getter; see `fee_bounds` and `MIN_LOT_SIZE`.
+#[derive(Debug, Clone)]
+pub struct ProtocolConfig {
+    /// Version reported by the configured package's `constants::current_version`.
+    pub current_version: u64,
+    /// DEEP charged by `pool::create_permissionless_pool`, in human units.
+    pub pool_creation_fee: f64,
+    /// Coin types the registry treats as stable; a pool is stable if both its coins are.
+    pub stable_coins: Vec<TypeTag>,
+}
+
+impl ProtocolConfig {
+    pub fn is_stable(&self, coin_type: &TypeTag) -> bool {
+        self.stable_coins.contains(coin_type)
+    }
+}
+
+impl TradingClient {
+    /// Read the protocol's version, pool creation fee and stable coin list from the
+    /// configured package and the registry.
+    pub async fn get_protocol_config(&self) -> Result<ProtocolConfig> {
+        let deep = self.config.coin("DEEP")?;
+        let package_id = self.config.package_id()?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        move_call(
+            &mut ptb,
+            package_id,
+            "constants",
+            "current_version",
+            vec![],
+            vec![],
+        )?;
+        move_call(
+            &mut ptb,
+            package_id,
+            "constants",
+            "pool_creation_fee",
+            vec![],
+            vec![],
+        )?;
+        let results = self.dev_inspect(ptb).await?;
+        let current_version: u64 = return_value(&results, 0, 0)?;
+        let pool_creation_fee: u64 = return_value(&results, 1, 0)?;
+
+        // TypeName strings carry the address without its 0x prefix.
+        let registry_inner = self.registry_inner().await?;
+        let stable_coins = vec_set_contents(&registry_inner["stable_coins"])
+            .iter()
+            .map(|name| TypeTag::from_str(&format!("0x{}", name.trim_start_matches("0x"))))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ProtocolConfig {
+            current_version,
+            pool_creation_fee: deep.from_units(pool_creation_fee),
+            stable_coins,
+        })
+    }
+
+    /// Reject a fee proposal locally if governance would abort on it. Fees are rates,
+    /// e.g. 0.0005 for 5 bps.
+    pub async fn validate_proposal(
+        &self,
+        pool_key: &str,
+        taker_fee: f64,
+        maker_fee: f64,
+    ) -> Result<()> {
+        if self.whitelisted(pool_key).await? {
+            return Err(anyhow!(
+                "Pool {} is whitelisted and does not accept fee proposals",
+                pool_key
+            ));
+        }
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let protocol = self.get_protocol_config().await?;
+        let stable =
+            protocol.is_stable(&base.type_tag()?) && protocol.is_stable(&quote.type_tag()?);
+        let bounds = fee_bounds(stable);
+
+        let taker_units = (taker_fee * FLOAT_SCALAR).round() as u64;
+        let maker_units = (maker_fee * FLOAT_SCALAR).round() as u64;
+        if taker_fee < 0.0
+            || taker_units < bounds.min_taker_fee
+            || taker_units > bounds.max_taker_fee
+        {
+            return Err(anyhow!(
+                "Taker fee {} is outside the allowed range {}..={} for pool {}",
+                taker_fee,
+                bounds.min_taker_fee as f64 / FLOAT_SCALAR,
+                bounds.max_taker_fee as f64 / FLOAT_SCALAR,
+                pool_key
+            ));
+        }
+        if maker_fee < 0.0
+            || maker_units < bounds.min_maker_fee
+            || maker_units > bounds.max_maker_fee
+        {
+            return Err(anyhow!(
+                "Maker fee {} is outside the allowed range {}..={} for pool {}",
+                maker_fee,
+                bounds.min_maker_fee as f64 / FLOAT_SCALAR,
+                bounds.max_maker_fee as f64 / FLOAT_SCALAR,
+                pool_key
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reject new pool parameters locally if `pool::create_pool` would abort on them.
+    /// `tick_size` is a price and `lot_size` and `min_size` are base quantities, all in
+    /// human units.
+    pub fn validate_pool_creation(
+        &self,
+        base_coin: &str,
+        quote_coin: &str,
+        tick_size: f64,
+        lot_size: f64,
+        min_size: f64,
+    ) -> Result<()> {
+        let base = self.config.coin(base_coin)?;
+        let quote = self.config.coin(quote_coin)?;
+        if base.type_tag()? == quote.type_tag()? {
+            return Err(anyhow!("A pool needs two different coins"));
+        }
+
+        let tick_units = price_to_units(tick_size, base, quote);
+        if !is_power_of_ten(tick_units) {
+            return Err(anyhow!(
+                "Tick size {} must be a power of ten in on-chain units, got {}",
+                tick_size,
+                tick_units
+            ));
+        }
+        let lot_units = base.to_units(lot_size);
+        if lot_units < MIN_LOT_SIZE || !is_power_of_ten(lot_units) {
+            return Err(anyhow!(
+                "Lot size {} must be a power of ten of at least {} {}",
+                lot_size,
+                base.from_units(MIN_LOT_SIZE),
+                base_coin
+            ));
+        }
+        let min_units = base.to_units(min_size);
+        if min_units == 0 || min_units % lot_units != 0 || !is_power_of_ten(min_units) {
+            return Err(anyhow!(
+                "Minimum size {} must be a power of ten and a multiple of the lot size {}",
+                min_size,
+                lot_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_power_of_ten(mut value: u64) -> bool {
+    if value == 0 {
+        return false;
+    }
+    while value % 10 == 0 {
+        value /= 10;
+    }
+    value == 1
+}
@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sui_sdk::{
+    rpc_types::{DevInspectResults, SuiObjectDataOptions, SuiParsedData},
+    types::{
+        TypeTag,
+        base_types::ObjectID,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        transaction::{Argument, TransactionKind},
+    },
+};
+
+use crate::account::MAX_CALLS_PER_INSPECT;
+use crate::client::TradingClient;
+use crate::config::{FLOAT_SCALAR, price_from_units, price_to_units};
+use crate::error::ClientError;
+use crate::transactions::move_call;
+use crate::types::{FullPoolInfo, Order, PoolBookParams, PoolTradeParams, QuantityOut, SwapParams};
+
+impl TradingClient {
+    /// Run a read-only PTB through dev-inspect.
+    ///
+    /// Providers that disable dev-inspect are reported as `ClientError::DevInspectUnavailable`
+    /// so callers can fall back to reading object contents directly.
+    pub async fn dev_inspect(
+        &self,
+        ptb: ProgrammableTransactionBuilder,
+    ) -> Result<DevInspectResults> {
+        self.dev_inspect_kind(TransactionKind::ProgrammableTransaction(ptb.finish()))
+            .await
+    }
+
+    pub async fn dev_inspect_kind(&self, tx: TransactionKind) -> Result<DevInspectResults> {
+        let results = self.dev_inspect_unchecked(tx).await?;
+        if let Some(error) = &results.error {
+            return Err(anyhow!("Dev inspect failed: {}", error));
+        }
+        Ok(results)
+    }
+
+    /// Like `dev_inspect_kind`, but returns the results of a PTB that aborts instead of
+    /// failing, for callers that treat an abort as an answer.
+    async fn dev_inspect_unchecked(&self, tx: TransactionKind) -> Result<DevInspectResults> {
+        let client = self.ctx.client()?;
+        let results = self
+            .with_rpc_timeout(async {
+                client
+                    .read_api()
+                    .dev_inspect_transaction_block(self.sender, tx, None, None, None)
+                    .await
+                    .map_err(|e| {
+                        let message = e.to_string();
+                        if is_unsupported(&message) {
+                            anyhow::Error::from(ClientError::DevInspectUnavailable(message))
+                        } else {
+                            anyhow::Error::from(e)
+                        }
+                    })
+            })
+            .await?;
+        Ok(results)
+    }
+
+    /// Fetch an object's type and its Move fields as JSON.
+    pub async fn object_fields(&self, id: ObjectID) -> Result<(String, Value)> {
+        self.try_object_fields(id)
+            .await?
+            .ok_or_else(|| anyhow!("Object {} not found", id))
+    }
+
+    /// Like `object_fields`, but `None` when the object does not exist.
+    pub async fn try_object_fields(&self, id: ObjectID) -> Result<Option<(String, Value)>> {
+        let response = self
+            .ctx
+            .client()?
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_type().with_content())
+            .await?;
+        let Some(data) = response.data else {
+            return Ok(None);
+        };
+        let type_ = data
+            .type_
+            .as_ref()
+            .map(|type_| type_.to_string())
+            .unwrap_or_default();
+        match data.content {
+            Some(SuiParsedData::MoveObject(object)) => {
+                Ok(Some((type_, object.fields.to_json_value())))
+            }
+            _ => Err(anyhow!("Object {} is not a Move object", id)),
+        }
+    }
+
+    /// Type arguments and leading `(pool[, balance_manager])` arguments shared by the
+    /// read-only `pool` functions.
+    pub async fn pool_args(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_id: Option<ObjectID>,
+    ) -> Result<(Vec<TypeTag>, Vec<Argument>)> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let mut args = vec![ptb.obj(self.ctx.shared_object(pool.id()?, false).await?)?];
+        if let Some(manager_id) = manager_id {
+            args.push(ptb.obj(self.ctx.shared_object(manager_id, false).await?)?);
+        }
+        Ok((vec![base.type_tag()?, quote.type_tag()?], args))
+    }
+
+    /// Whether the pool is whitelisted, i.e. trades on it pay no fees.
+    pub async fn whitelisted(&self, pool_key: &str) -> Result<bool> {
+        if let Some(whitelisted) = self.ctx.whitelisted(self.config.pool(pool_key)?.id()?) {
+            return Ok(whitelisted);
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "whitelisted",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        return_value(&results, 0, 0)
+    }
+
+    /// Get the DEEP held in the pool's vault, which covers fees on whitelisted pools.
+    pub async fn get_deep_reserve(&self, pool_key: &str) -> Result<f64> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "vault_balances",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        let deep_balance: u64 = return_value(&results, 0, 2)?;
+        Ok(self.config.coin("DEEP")?.from_units(deep_balance))
+    }
+
+    /// Whether the pool's DEEP reserve covers the fee DeepBook estimates for a swap of
+    /// `params.amount`, base in when `base_in`. Meaningful on whitelisted pools, which
+    /// take swap fees from the reserve rather than the trader.
+    pub async fn has_deep_reserve(&self, params: &SwapParams, base_in: bool) -> Result<bool> {
+        let estimate = if base_in {
+            self.get_quote_quantity_out(&params.pool_key, params.amount)
+                .await?
+        } else {
+            self.get_base_quantity_out(&params.pool_key, params.amount)
+                .await?
+        };
+        Ok(self.get_deep_reserve(&params.pool_key).await? >= estimate.deep_required)
+    }
+
+    /// Get a resting order by ID.
+    pub async fn get_order(&self, pool_key: &str, order_id: u128) -> Result<Order> {
+        if let Some(order) = self.ctx.order(self.config.pool(pool_key)?.id()?, order_id) {
+            return Ok(order);
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, mut args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        args.push(ptb.pure(order_id)?);
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "get_order",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        return_value(&results, 0, 0)
+    }
+
+    /// Get several resting orders in one call, in the order of `order_ids`.
+    pub async fn get_orders(&self, pool_key: &str, order_ids: &[u128]) -> Result<Vec<Order>> {
+        if order_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, mut args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        args.push(ptb.pure(order_ids.to_vec())?);
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "get_orders",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        return_value(&results, 0, 0)
+    }
+
+    /// Check which of `order_ids` still rest on the pool's book; filled, cancelled and
+    /// expired-and-removed orders map to `false`.
+    ///
+    /// `pool::get_order` aborts on a missing order, so the IDs are looked up in batches
+    /// and a batch that aborts is split in half until the missing orders are isolated.
+    /// A mostly-live list costs a handful of dev-inspects rather than one per ID.
+    pub async fn are_orders_live(
+        &self,
+        pool_key: &str,
+        order_ids: &[u128],
+    ) -> Result<HashMap<u128, bool>> {
+        let mut live = HashMap::with_capacity(order_ids.len());
+        let mut pending: Vec<&[u128]> = order_ids.chunks(MAX_CALLS_PER_INSPECT).collect();
+        while let Some(batch) = pending.pop() {
+            if self.orders_exist(pool_key, batch).await? {
+                live.extend(batch.iter().map(|id| (*id, true)));
+            } else if let [order_id] = batch {
+                live.insert(*order_id, false);
+            } else {
+                let (left, right) = batch.split_at(batch.len() / 2);
+                pending.push(left);
+                pending.push(right);
+            }
+        }
+        Ok(live)
+    }
+
+    /// Whether every order in `order_ids` is on the book, in one dev-inspect.
+    async fn orders_exist(&self, pool_key: &str, order_ids: &[u128]) -> Result<bool> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        for order_id in order_ids {
+            let mut call_args = args.clone();
+            call_args.push(ptb.pure(*order_id)?);
+            move_call(
+                &mut ptb,
+                self.config.package_id()?,
+                "pool",
+                "get_order",
+                type_args.clone(),
+                call_args,
+            )?;
+        }
+        let tx = TransactionKind::ProgrammableTransaction(ptb.finish());
+        Ok(self.dev_inspect_unchecked(tx).await?.error.is_none())
+    }
+
+    /// Get the pool's tick, lot and minimum order size, in on-chain units.
+    pub async fn get_pool_book_params(&self, pool_key: &str) -> Result<PoolBookParams> {
+        if let Some(book_params) = self.ctx.book_params(self.config.pool(pool_key)?.id()?) {
+            return Ok(book_params);
+        }
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "pool_book_params",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        Ok(PoolBookParams {
+            tick_size: return_value(&results, 0, 0)?,
+            lot_size: return_value(&results, 0, 1)?,
+            min_size: return_value(&results, 0, 2)?,
+        })
+    }
+
+    /// Get the pool's current fee rates and stake requirement, in on-chain units.
+    pub async fn get_pool_trade_params(&self, pool_key: &str) -> Result<PoolTradeParams> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "pool_trade_params",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        Ok(PoolTradeParams {
+            taker_fee: return_value(&results, 0, 0)?,
+            maker_fee: return_value(&results, 0, 1)?,
+            stake_required: return_value(&results, 0, 2)?,
+        })
+    }
+
+    /// Get a pool's coins, sizes, fees and whitelist status, read in a single dev-inspect.
+    pub async fn get_pool_info(&self, pool_key: &str) -> Result<FullPoolInfo> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        for function in ["whitelisted", "pool_book_params", "pool_trade_params"] {
+            move_call(
+                &mut ptb,
+                self.config.package_id()?,
+                "pool",
+                function,
+                type_args.clone(),
+                args.clone(),
+            )?;
+        }
+
+        let results = self.dev_inspect(ptb).await?;
+        let book_params = PoolBookParams {
+            tick_size: return_value(&results, 1, 0)?,
+            lot_size: return_value(&results, 1, 1)?,
+            min_size: return_value(&results, 1, 2)?,
+        };
+        let trade_params = PoolTradeParams {
+            taker_fee: return_value(&results, 2, 0)?,
+            maker_fee: return_value(&results, 2, 1)?,
+            stake_required: return_value(&results, 2, 2)?,
+        };
+
+        Ok(FullPoolInfo {
+            pool_key: pool_key.to_string(),
+            pool_id: pool.id()?,
+            base_coin: pool.base_coin.clone(),
+            quote_coin: pool.quote_coin.clone(),
+            base_type: self.config.coin_type(&pool.base_coin)?,
+            quote_type: self.config.coin_type(&pool.quote_coin)?,
+            base_decimals: base.decimals()?,
+            quote_decimals: quote.decimals()?,
+            tick_size: price_from_units(book_params.tick_size, base, quote),
+            lot_size: base.from_units(book_params.lot_size),
+            min_size: base.from_units(book_params.min_size),
+            taker_fee: trade_params.taker_fee as f64 / FLOAT_SCALAR,
+            maker_fee: trade_params.maker_fee as f64 / FLOAT_SCALAR,
+            stake_required: deep.from_units(trade_params.stake_required),
+            whitelisted: return_value(&results, 0, 0)?,
+            book_params,
+            trade_params,
+        })
+    }
+
+    /// DEEP needed by an order of `quantity` base at `price`, as (taker, maker) fees
+    /// in human units.
+    pub async fn get_order_deep_required(
+        &self,
+        pool_key: &str,
+        quantity: f64,
+        price: f64,
+    ) -> Result<(f64, f64)> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, mut args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        args.push(ptb.pure(base.to_units(quantity))?);
+        args.push(ptb.pure(price_to_units(price, base, quote))?);
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            "get_order_deep_required",
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        let taker: u64 = return_value(&results, 0, 0)?;
+        let maker: u64 = return_value(&results, 0, 1)?;
+        Ok((deep.from_units(taker), deep.from_units(maker)))
+    }
+
+    /// Quote a swap of `base_quantity` base for quote.
+    pub async fn get_quote_quantity_out(
+        &self,
+        pool_key: &str,
+        base_quantity: f64,
+    ) -> Result<QuantityOut> {
+        self.quantity_out(pool_key, "get_quote_quantity_out", base_quantity, true)
+            .await
+    }
+
+    /// Quote a swap of `quote_quantity` quote for base.
+    pub async fn get_base_quantity_out(
+        &self,
+        pool_key: &str,
+        quote_quantity: f64,
+    ) -> Result<QuantityOut> {
+        self.quantity_out(pool_key, "get_base_quantity_out", quote_quantity, false)
+            .await
+    }
+
+    async fn quantity_out(
+        &self,
+        pool_key: &str,
+        function: &str,
+        quantity: f64,
+        base_in: bool,
+    ) -> Result<QuantityOut> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+        let input = if base_in { base } else { quote };
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let (type_args, mut args) = self.pool_args(&mut ptb, pool_key, None).await?;
+        args.push(ptb.pure(input.to_units(quantity))?);
+        args.push(ptb.obj(self.ctx.clock())?);
+        move_call(
+            &mut ptb,
+            self.config.package_id()?,
+            "pool",
+            function,
+            type_args,
+            args,
+        )?;
+
+        let results = self.dev_inspect(ptb).await?;
+        let base_out: u64 = return_value(&results, 0, 0)?;
+        let quote_out: u64 = return_value(&results, 0, 1)?;
+        let deep_required: u64 = return_value(&results, 0, 2)?;
+
+        Ok(QuantityOut {
+            base_out: base.from_units(base_out),
+            quote_out: quote.from_units(quote_out),
+            deep_required: deep.from_units(deep_required),
+        })
+    }
+}
+
+/// Whether an RPC error means the method is disabled rather than that the call failed.
+fn is_unsupported(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "method not found",
+        "-32601",
+        "not supported",
+        "unsupported",
+        "disabled",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+}
+
+/// Decode the `index`th return value of the `command`th command of a dev-inspect run.
+pub fn return_value<T: DeserializeOwned>(
+    results: &DevInspectResults,
+    command: usize,
+    index: usize,
+) -> Result<T> {
+    let (bytes, _) = results
+        .results
+        .as_ref()
+        .and_then(|results| results.get(command))
+        .and_then(|result| result.return_values.get(index))
+        .ok_or_else(|| {
+            anyhow!(
+                "Missing return value {} of command {} in dev inspect results",
+                index,
+                command
+            )
+        })?;
+    Ok(bcs::from_bytes(bytes)?)
+}
@@ -0,0 +1,25 @@
+pub mod balance_manager;
+pub mod deep_book;
+
+use anyhow::Result;
+use sui_sdk::types::{
+    Identifier, TypeTag, base_types::ObjectID,
+    programmable_transaction_builder::ProgrammableTransactionBuilder, transaction::Argument,
+};
+
+pub fn move_call(
+    ptb: &mut ProgrammableTransactionBuilder,
+    package: ObjectID,
+    module: &str,
+    function: &str,
+    type_arguments: Vec<TypeTag>,
+    arguments: Vec<Argument>,
+) -> Result<Argument> {
+    Ok(ptb.programmable_move_call(
+        package,
+        Identifier::new(module)?,
+        Identifier::new(function)?,
+        type_arguments,
+        arguments,
+    ))
+}
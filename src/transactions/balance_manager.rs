@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use sui_sdk::types::{
+    base_types::{ObjectID, SuiAddress},
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::Argument,
+};
+
+use crate::client::TradingClient;
+use crate::transactions::move_call;
+use crate::types::BalanceSource;
+
+impl TradingClient {
+    /// Generate a TradeProof for the manager, using its trade cap when one is configured.
+    ///
+    /// With a trade cap the proof is generated as a trader, so a delegate that holds the
+    /// cap but does not own the manager can place, modify and cancel orders; the sender
+    /// must then be the cap holder, since only the owner of the cap can pass it in.
+    ///
+    /// A proof is an ordinary PTB result: any number of calls in the same PTB may borrow
+    /// it, but it does not outlive the transaction, so every new PTB needs its own. The
+    /// `*_with_proof` variants of the trading calls take one generated earlier.
+    pub async fn generate_proof(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let manager = self.balance_manager(manager_key)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+
+        match manager.trade_cap.as_deref() {
+            Some(trade_cap) => {
+                let trade_cap = ObjectID::from_hex_literal(trade_cap)?;
+                let trade_cap_arg = ptb.obj(self.ctx.owned_object(trade_cap).await?)?;
+                move_call(
+                    ptb,
+                    self.config.package_id()?,
+                    "balance_manager",
+                    "generate_proof_as_trader",
+                    vec![],
+                    vec![manager_arg, trade_cap_arg],
+                )
+            }
+            None => move_call(
+                ptb,
+                self.config.package_id()?,
+                "balance_manager",
+                "generate_proof_as_owner",
+                vec![],
+                vec![manager_arg],
+            ),
+        }
+    }
+
+    /// Mint a trade cap for the manager and send it to `recipient`, delegating trading
+    /// to that address. Only the manager's owner can mint caps.
+    pub async fn mint_trade_cap(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        recipient: SuiAddress,
+    ) -> Result<()> {
+        let manager_key = self.manager_key(manager_key)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let trade_cap = move_call(
+            ptb,
+            self.config.package_id()?,
+            "balance_manager",
+            "mint_trade_cap",
+            vec![],
+            vec![manager_arg],
+        )?;
+        ptb.transfer_args(recipient, vec![trade_cap]);
+        Ok(())
+    }
+
+    /// Deposit several coins into the manager in one PTB. Amounts are in human units;
+    /// repeated coins are combined into a single deposit.
+    ///
+    /// The sender's wallet balance of every coin is checked before anything is added
+    /// to the PTB, so a shortfall is reported by coin instead of aborting on-chain.
+    /// SUI deposits are split from the gas coin; the check does not include gas, which
+    /// the PTB's budget decides once it is complete.
+    pub async fn deposit_many(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        deposits: &[(&str, f64)],
+    ) -> Result<()> {
+        let mut required: BTreeMap<&str, u64> = BTreeMap::new();
+        for (coin_key, amount) in deposits {
+            let coin = self.config.coin(coin_key)?;
+            let coin_key = self.config.coin_key(coin_key).unwrap_or(coin_key);
+            *required.entry(coin_key).or_default() += coin.to_units(*amount);
+        }
+        for (coin_key, amount) in &required {
+            let coin = self.config.coin(coin_key)?;
+            self.ensure_balance(coin_key, coin.from_units(*amount), BalanceSource::Wallet)
+                .await?;
+        }
+
+        for (coin_key, amount) in &required {
+            let coin = self.config.coin(coin_key)?;
+            let deposit = self
+                .ctx
+                .coin_with_balance(ptb, self.sender, coin, *amount)
+                .await?;
+            self.deposit_coin(ptb, manager_key, coin_key, deposit)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Deposit a coin produced earlier in the PTB into the manager, consuming it whole.
+    pub async fn deposit_coin(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        coin_key: &str,
+        coin: Argument,
+    ) -> Result<Argument> {
+        let coin_type = self.config.coin(coin_key)?.type_tag()?;
+        let manager_id = self.manager_id(manager_key)?;
+        let manager_arg = ptb.obj(self.ctx.shared_object(manager_id, true).await?)?;
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "balance_manager",
+            "deposit",
+            vec![coin_type],
+            vec![manager_arg, coin],
+        )
+    }
+
+    /// Withdraw `amount` (in the coin's smallest unit) of `coin_key` from the manager.
+    pub async fn withdraw_from_manager(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        coin_key: &str,
+        amount: u64,
+    ) -> Result<Argument> {
+        if self.balance_checks {
+            let manager_key = self.manager_key(manager_key)?;
+            self.ensure_balance(
+                coin_key,
+                self.config.coin(coin_key)?.from_units(amount),
+                BalanceSource::Manager(manager_key.to_string()),
+            )
+            .await?;
+        }
+        self.withdraw_released(ptb, manager_key, coin_key, amount)
+            .await
+    }
+
+    /// `withdraw_from_manager` without the balance check, for funds that an earlier call
+    /// in the same PTB settles or unlocks into the manager and the chain cannot see yet.
+    /// Callers check the settled or locked amounts themselves.
+    pub(crate) async fn withdraw_released(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        coin_key: &str,
+        amount: u64,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let coin = self.config.coin(coin_key)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let amount = ptb.pure(amount)?;
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "balance_manager",
+            "withdraw",
+            vec![coin.type_tag()?],
+            vec![manager_arg, amount],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use deepbookv3::types::BalanceManager;
+    use sui_sdk::types::{
+        base_types::SequenceNumber,
+        digests::ObjectDigest,
+        transaction::{CallArg, Command, ObjectArg},
+    };
+
+    use super::*;
+    use crate::client::test_support::{MANAGER_ID, MANAGER_KEY, move_call_names, offline_client};
+    use crate::context::BuildContext;
+
+    const TRADE_CAP_ID: &str = "0x00000000000000000000000000000000000000000000000000000000000000ca";
+
+    #[tokio::test]
+    async fn a_minted_trade_cap_lets_another_address_cancel() {
+        let trader = SuiAddress::from(ObjectID::from_single_byte(0xaa));
+
+        // The owner mints a cap and sends it to the trader.
+        let owner = offline_client(BuildContext::fixed());
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        owner.mint_trade_cap(&mut ptb, None, trader).await.unwrap();
+        let pt = ptb.finish();
+        assert_eq!(move_call_names(&pt), ["balance_manager::mint_trade_cap"]);
+        let Some(Command::TransferObjects(caps, Argument::Input(recipient))) = pt.commands.last()
+        else {
+            panic!("expected the cap transfer, got {:?}", pt.commands.last());
+        };
+        assert_eq!(caps, &[Argument::Result(0)]);
+        assert_eq!(
+            pt.inputs[*recipient as usize],
+            CallArg::Pure(bcs::to_bytes(&trader).unwrap())
+        );
+
+        // The trader configures the same manager with the cap it received and cancels.
+        let cap = (
+            ObjectID::from_hex_literal(TRADE_CAP_ID).unwrap(),
+            SequenceNumber::from_u64(3),
+            ObjectDigest::MIN,
+        );
+        let mut delegate = offline_client(BuildContext::fixed().with_owned_object(cap));
+        delegate.sender = trader;
+        delegate.balance_managers.insert(
+            MANAGER_KEY.to_string(),
+            BalanceManager {
+                address: MANAGER_ID,
+                trade_cap: Some(TRADE_CAP_ID.into()),
+                deposit_cap: None,
+                withdraw_cap: None,
+            },
+        );
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        delegate
+            .cancel_order(&mut ptb, "SUI_DBUSDC", None, 42)
+            .await
+            .unwrap();
+        let pt = ptb.finish();
+
+        assert_eq!(
+            move_call_names(&pt),
+            [
+                "balance_manager::generate_proof_as_trader",
+                "pool::cancel_order"
+            ]
+        );
+        let Command::MoveCall(proof) = &pt.commands[0] else {
+            panic!("expected the proof, got {:?}", pt.commands[0]);
+        };
+        let Argument::Input(cap_input) = proof.arguments[1] else {
+            panic!("trade cap is not an input: {:?}", proof.arguments[1]);
+        };
+        assert_eq!(
+            pt.inputs[cap_input as usize],
+            CallArg::Object(ObjectArg::ImmOrOwnedObject(cap))
+        );
+        let Command::MoveCall(cancel) = &pt.commands[1] else {
+            panic!("expected the cancel, got {:?}", pt.commands[1]);
+        };
+        assert_eq!(cancel.arguments[2], Argument::Result(0));
+    }
+}
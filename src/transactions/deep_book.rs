@@ -0,0 +1,1571 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, anyhow};
+use sui_sdk::types::{
+    SUI_FRAMEWORK_PACKAGE_ID, programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::Argument,
+};
+
+use crate::client::TradingClient;
+use crate::config::{FLOAT_SCALAR, price_from_units, price_to_units};
+use crate::context::nested_result;
+use crate::transactions::move_call;
+use crate::types::{
+    BalanceSource, MAX_TIMESTAMP, OrderType, PlaceLimitOrderParams, PlaceMarketOrderParams,
+    SelfMatchingOptions, Side, SwapParams, SweepReport, TwoSidedQuote,
+};
+
+impl TradingClient {
+    /// Place a limit order through the manager's TradeProof.
+    pub async fn place_limit_order(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &PlaceLimitOrderParams,
+    ) -> Result<Argument> {
+        let proof = self
+            .generate_proof(ptb, params.balance_manager_key.as_deref())
+            .await?;
+        self.place_limit_order_with_proof(ptb, params, proof).await
+    }
+
+    /// Like `place_limit_order`, reusing a TradeProof generated earlier in the same PTB
+    /// for the order's manager.
+    pub async fn place_limit_order_with_proof(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &PlaceLimitOrderParams,
+        proof: Argument,
+    ) -> Result<Argument> {
+        if self.balance_checks {
+            self.ensure_order_balance(params).await?;
+        }
+        self.place_funded_limit_order(ptb, params, proof).await
+    }
+
+    /// Check the order's manager holds what the order locks: quote worth price times
+    /// quantity for a bid, the base quantity for an ask. Fees are not included.
+    pub async fn ensure_order_balance(&self, params: &PlaceLimitOrderParams) -> Result<()> {
+        let pool = self.config.pool(&params.pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let manager_key = self.manager_key(params.balance_manager_key.as_deref())?;
+
+        let quantity = base.from_units(params.quantity_units(base));
+        let (coin_key, required) = match params.side {
+            Side::Bid => (
+                &pool.quote_coin,
+                price_from_units(params.price_units(base, quote), base, quote) * quantity,
+            ),
+            Side::Ask => (&pool.base_coin, quantity),
+        };
+        self.ensure_balance(
+            coin_key,
+            required,
+            BalanceSource::Manager(manager_key.to_string()),
+        )
+        .await
+    }
+
+    /// `place_limit_order_with_proof` without the balance check, for orders funded by a
+    /// deposit or cancel earlier in the same PTB that the chain cannot see yet.
+    pub(crate) async fn place_funded_limit_order(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &PlaceLimitOrderParams,
+        proof: Argument,
+    ) -> Result<Argument> {
+        self.ensure_fee_option(&params.pool_key, params.pay_with_deep)
+            .await?;
+        self.validate_limit_order(params).await?;
+
+        let pool = self.config.pool(&params.pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let manager_key = self.manager_key(params.balance_manager_key.as_deref())?;
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let client_order_id = ptb.pure(params.client_order_id)?;
+        let order_type = ptb.pure(params.order_type.unwrap_or(OrderType::NoRestriction) as u8)?;
+        let self_matching_option = ptb.pure(
+            params
+                .self_matching_option
+                .unwrap_or(SelfMatchingOptions::SelfMatchingAllowed) as u8,
+        )?;
+        let price = ptb.pure(params.price_units(base, quote))?;
+        let quantity = ptb.pure(params.quantity_units(base))?;
+        let is_bid = ptb.pure(params.side.is_bid())?;
+        let pay_with_deep = ptb.pure(params.pay_with_deep)?;
+        let expiration = ptb.pure(params.expiration.unwrap_or(MAX_TIMESTAMP))?;
+        let clock = ptb.obj(self.ctx.clock())?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "place_limit_order",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![
+                pool_arg,
+                manager_arg,
+                proof,
+                client_order_id,
+                order_type,
+                self_matching_option,
+                price,
+                quantity,
+                is_bid,
+                pay_with_deep,
+                expiration,
+                clock,
+            ],
+        )
+    }
+
+    /// Quote both sides of a pool in one PTB: place `bid` and `ask` for the same manager
+    /// through a single TradeProof, so either both orders go on the book or neither does.
+    ///
+    /// The bid must be priced strictly below the ask. Any part of either order that
+    /// crosses the book fills immediately and is credited to the manager's balance.
+    pub async fn place_two_sided_quote(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        bid: &PlaceLimitOrderParams,
+        ask: &PlaceLimitOrderParams,
+    ) -> Result<TwoSidedQuote> {
+        if bid.side != Side::Bid || ask.side != Side::Ask {
+            return Err(anyhow!("A two-sided quote needs one bid and one ask"));
+        }
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        if bid.price_units(base, quote) >= ask.price_units(base, quote) {
+            return Err(anyhow!(
+                "Bid price {} must be below ask price {} on {}",
+                bid.price,
+                ask.price,
+                pool_key
+            ));
+        }
+
+        let bid = PlaceLimitOrderParams {
+            pool_key: pool_key.to_string(),
+            balance_manager_key: Some(manager_key.to_string()),
+            ..bid.clone()
+        };
+        let ask = PlaceLimitOrderParams {
+            pool_key: pool_key.to_string(),
+            balance_manager_key: Some(manager_key.to_string()),
+            ..ask.clone()
+        };
+        let proof = self.generate_proof(ptb, Some(manager_key)).await?;
+        Ok(TwoSidedQuote {
+            bid: self.place_limit_order_with_proof(ptb, &bid, proof).await?,
+            ask: self.place_limit_order_with_proof(ptb, &ask, proof).await?,
+        })
+    }
+
+    /// Fund a limit order from a wallet coin: deposit `coin` (of `coin_key`) into the
+    /// order's manager and place the order in the same PTB. The whole coin is deposited;
+    /// whatever the order does not lock stays in the manager.
+    pub async fn place_limit_order_with_coin(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &PlaceLimitOrderParams,
+        coin_key: &str,
+        coin: Argument,
+    ) -> Result<Argument> {
+        self.deposit_coin(ptb, params.balance_manager_key.as_deref(), coin_key, coin)
+            .await?;
+        let proof = self
+            .generate_proof(ptb, params.balance_manager_key.as_deref())
+            .await?;
+        self.place_funded_limit_order(ptb, params, proof).await
+    }
+
+    /// Place a market order through the manager's TradeProof.
+    pub async fn place_market_order(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &PlaceMarketOrderParams,
+    ) -> Result<Argument> {
+        let proof = self
+            .generate_proof(ptb, params.balance_manager_key.as_deref())
+            .await?;
+        self.place_market_order_with_proof(ptb, params, proof).await
+    }
+
+    /// Like `place_market_order`, reusing a TradeProof generated earlier in the same PTB
+    /// for the order's manager.
+    pub async fn place_market_order_with_proof(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &PlaceMarketOrderParams,
+        proof: Argument,
+    ) -> Result<Argument> {
+        self.ensure_fee_option(&params.pool_key, params.pay_with_deep)
+            .await?;
+
+        let pool = self.config.pool(&params.pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let manager_key = self.manager_key(params.balance_manager_key.as_deref())?;
+        // What a market bid spends depends on the book, so only asks are checked.
+        if self.balance_checks && params.side == Side::Ask {
+            self.ensure_balance(
+                &pool.base_coin,
+                base.from_units(params.quantity_units(base)),
+                BalanceSource::Manager(manager_key.to_string()),
+            )
+            .await?;
+        }
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let client_order_id = ptb.pure(params.client_order_id)?;
+        let self_matching_option = ptb.pure(
+            params
+                .self_matching_option
+                .unwrap_or(SelfMatchingOptions::SelfMatchingAllowed) as u8,
+        )?;
+        let quantity = ptb.pure(params.quantity_units(base))?;
+        let is_bid = ptb.pure(params.side.is_bid())?;
+        let pay_with_deep = ptb.pure(params.pay_with_deep)?;
+        let clock = ptb.obj(self.ctx.clock())?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "place_market_order",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![
+                pool_arg,
+                manager_arg,
+                proof,
+                client_order_id,
+                self_matching_option,
+                quantity,
+                is_bid,
+                pay_with_deep,
+                clock,
+            ],
+        )
+    }
+
+    /// Cancel one of the manager's orders.
+    pub async fn cancel_order(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        order_id: u128,
+    ) -> Result<Argument> {
+        let proof = self.generate_proof(ptb, manager_key).await?;
+        self.cancel_order_with_proof(ptb, pool_key, manager_key, order_id, proof)
+            .await
+    }
+
+    /// Like `cancel_order`, reusing a TradeProof generated earlier in the same PTB.
+    pub async fn cancel_order_with_proof(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        order_id: u128,
+        proof: Argument,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let order_id = ptb.pure(order_id)?;
+        let clock = ptb.obj(self.ctx.clock())?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "cancel_order",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![pool_arg, manager_arg, proof, order_id, clock],
+        )
+    }
+
+    /// Cancel all of the manager's orders on a pool.
+    pub async fn cancel_all_orders(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<Argument> {
+        let proof = self.generate_proof(ptb, manager_key).await?;
+        self.cancel_all_orders_with_proof(ptb, pool_key, manager_key, proof)
+            .await
+    }
+
+    /// Like `cancel_all_orders`, reusing a TradeProof generated earlier in the same PTB.
+    pub async fn cancel_all_orders_with_proof(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        proof: Argument,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let clock = ptb.obj(self.ctx.clock())?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "cancel_all_orders",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![pool_arg, manager_arg, proof, clock],
+        )
+    }
+
+    /// Push out an order's expiration to `new_expire_ms`.
+    ///
+    /// DeepBook's `modify_order` can only reduce quantity, so this cancels the order and
+    /// places its remaining quantity again at the same price with the new expiration.
+    /// The replacement gets a new order ID and joins the back of the queue at its price
+    /// level, losing the original's time priority.
+    ///
+    /// DeepBook does not store an order's restriction, so pass the original's as
+    /// `order_type`; only `NoRestriction` (the default) and `PostOnly` orders rest on
+    /// the book. Fails if the order belongs to another manager or if less than the
+    /// pool's minimum size of it remains.
+    pub async fn extend_expiration(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        order_id: u128,
+        new_expire_ms: u64,
+        order_type: Option<OrderType>,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let order = self.get_order(pool_key, order_id).await?;
+
+        if new_expire_ms <= self.ctx.now_ms() {
+            return Err(anyhow!(
+                "New expiration {} is not in the future",
+                new_expire_ms
+            ));
+        }
+        if matches!(
+            order_type,
+            Some(OrderType::ImmediateOrCancel | OrderType::FillOrKill)
+        ) {
+            return Err(anyhow!(
+                "Order type {:?} never rests on the book",
+                order_type
+            ));
+        }
+        let manager_id = self.balance_manager_id(manager_key)?;
+        if order.balance_manager_id != manager_id {
+            return Err(anyhow!(
+                "Order {} belongs to balance manager {}, not {}",
+                order_id,
+                order.balance_manager_id,
+                manager_id
+            ));
+        }
+        let min_size = self.get_pool_book_params(pool_key).await?.min_size;
+        if order.remaining_quantity() < min_size {
+            return Err(anyhow!(
+                "Only {} of order {} remains, below the minimum size {} for pool {}",
+                base.from_units(order.remaining_quantity()),
+                order_id,
+                base.from_units(min_size),
+                pool_key
+            ));
+        }
+
+        let proof = self.generate_proof(ptb, Some(manager_key)).await?;
+        self.cancel_order_with_proof(ptb, pool_key, Some(manager_key), order_id, proof)
+            .await?;
+        // The cancel above unlocks the funds only once the PTB runs, so the replacement
+        // skips the balance check.
+        self.place_funded_limit_order(
+            ptb,
+            &PlaceLimitOrderParams {
+                pool_key: pool_key.to_string(),
+                balance_manager_key: Some(manager_key.to_string()),
+                client_order_id: order.client_order_id,
+                price: price_from_units(order.price(), base, quote),
+                quantity: base.from_units(order.remaining_quantity()),
+                price_units: Some(order.price()),
+                quantity_units: Some(order.remaining_quantity()),
+                side: order.side(),
+                expiration: Some(new_expire_ms),
+                order_type,
+                self_matching_option: None,
+                pay_with_deep: order.fee_is_deep,
+            },
+            proof,
+        )
+        .await
+    }
+
+    /// Swap an exact amount of the base coin for the quote coin.
+    /// Returns the (base, quote, deep) coins left over by the swap.
+    pub async fn swap_exact_base_for_quote(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &SwapParams,
+    ) -> Result<(Argument, Argument, Argument)> {
+        self.swap(ptb, params, true, None).await
+    }
+
+    /// Swap an exact amount of the quote coin for the base coin.
+    /// Returns the (base, quote, deep) coins left over by the swap.
+    pub async fn swap_exact_quote_for_base(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &SwapParams,
+    ) -> Result<(Argument, Argument, Argument)> {
+        self.swap(ptb, params, false, None).await
+    }
+
+    /// Swap base for `quote_out` of the quote coin, spending at most `max_base_in` base.
+    /// Returns the quote coin; unspent base and DEEP go back to the sender.
+    ///
+    /// DeepBook only swaps exact inputs, so this is an approximation: the pool's quotes are
+    /// searched for the smallest lot-aligned base input that yields `quote_out`, and that
+    /// input is swapped with `quote_out` as the minimum output. The result can exceed
+    /// `quote_out` by up to one lot's worth, and if the book moves against the quote
+    /// before execution the swap aborts rather than delivering less. Fees are paid in DEEP.
+    pub async fn swap_base_for_exact_quote(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        quote_out: f64,
+        max_base_in: f64,
+    ) -> Result<Argument> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let lot_size = self.get_pool_book_params(pool_key).await?.lot_size;
+        let target = quote.to_units(quote_out);
+
+        // Smallest number of lots whose quote reaches the target; quotes grow with input.
+        let quote_for = async |lots: u64| -> Result<u64> {
+            let estimate = self
+                .get_quote_quantity_out(pool_key, base.from_units(lots * lot_size))
+                .await?;
+            Ok(quote.to_units(estimate.quote_out))
+        };
+        let mut high = base.to_units(max_base_in) / lot_size;
+        if high == 0 || quote_for(high).await? < target {
+            return Err(anyhow!(
+                "Swapping up to {} {} on {} cannot yield {} {}",
+                max_base_in,
+                pool.base_coin,
+                pool_key,
+                quote_out,
+                pool.quote_coin
+            ));
+        }
+        let mut low = 0;
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if quote_for(mid).await? >= target {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+
+        let base_in = base.from_units(high * lot_size);
+        let estimate = self.get_quote_quantity_out(pool_key, base_in).await?;
+        let params = SwapParams {
+            pool_key: pool_key.to_string(),
+            amount: base_in,
+            deep_amount: estimate.deep_required,
+            min_out: quote_out,
+            pay_with_deep: true,
+        };
+        let (base_left, quote_coin, deep_left) = self.swap(ptb, &params, true, None).await?;
+        ptb.transfer_args(self.sender, vec![base_left, deep_left]);
+        Ok(quote_coin)
+    }
+
+    /// Like `swap_exact_base_for_quote`, but withdraws exactly the estimated DEEP fee from
+    /// the manager instead of splitting it from the sender's coins. `deep_amount` is ignored.
+    pub async fn swap_exact_base_for_quote_with_manager_deep(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &SwapParams,
+        manager_key: Option<&str>,
+    ) -> Result<(Argument, Argument, Argument)> {
+        let manager_key = self.manager_key(manager_key)?;
+        self.swap(ptb, params, true, Some(manager_key)).await
+    }
+
+    /// Like `swap_exact_quote_for_base`, but withdraws exactly the estimated DEEP fee from
+    /// the manager instead of splitting it from the sender's coins. `deep_amount` is ignored.
+    pub async fn swap_exact_quote_for_base_with_manager_deep(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &SwapParams,
+        manager_key: Option<&str>,
+    ) -> Result<(Argument, Argument, Argument)> {
+        let manager_key = self.manager_key(manager_key)?;
+        self.swap(ptb, params, false, Some(manager_key)).await
+    }
+
+    /// Shared swap body. With `deep_manager` set, the DEEP fee is withdrawn from that
+    /// manager; any unused DEEP comes back with the output coins, not to the manager.
+    async fn swap(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        params: &SwapParams,
+        base_in: bool,
+        deep_manager: Option<&str>,
+    ) -> Result<(Argument, Argument, Argument)> {
+        let deep_units = self
+            .swap_deep_units(params, base_in, deep_manager.is_some())
+            .await?;
+
+        let pool = self.config.pool(&params.pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+
+        let (input, output) = if base_in {
+            (base, quote)
+        } else {
+            (quote, base)
+        };
+        if self.balance_checks {
+            let input_key = if base_in {
+                &pool.base_coin
+            } else {
+                &pool.quote_coin
+            };
+            self.ensure_balance(input_key, params.amount, BalanceSource::Wallet)
+                .await?;
+            // DEEP withdrawn from a manager is checked by `withdraw_from_manager`.
+            if deep_units > 0 && deep_manager.is_none() {
+                self.ensure_balance("DEEP", deep.from_units(deep_units), BalanceSource::Wallet)
+                    .await?;
+            }
+        }
+        let input_coin = self
+            .ctx
+            .coin_with_balance(ptb, self.sender, input, input.to_units(params.amount))
+            .await?;
+        // An empty DEEP coin makes the pool take its fee from the input coin instead.
+        let deep_coin = if deep_units > 0 {
+            match deep_manager {
+                Some(manager_key) => {
+                    self.withdraw_from_manager(ptb, Some(manager_key), "DEEP", deep_units)
+                        .await?
+                }
+                None => {
+                    self.ctx
+                        .coin_with_balance(ptb, self.sender, deep, deep_units)
+                        .await?
+                }
+            }
+        } else {
+            move_call(
+                ptb,
+                SUI_FRAMEWORK_PACKAGE_ID,
+                "coin",
+                "zero",
+                vec![deep.type_tag()?],
+                vec![],
+            )?
+        };
+
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let min_out = ptb.pure(output.to_units(params.min_out))?;
+        let clock = ptb.obj(self.ctx.clock())?;
+
+        let function = if base_in {
+            "swap_exact_base_for_quote"
+        } else {
+            "swap_exact_quote_for_base"
+        };
+        let result = move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            function,
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![pool_arg, input_coin, deep_coin, min_out, clock],
+        )?;
+
+        Ok((
+            nested_result(result, 0),
+            nested_result(result, 1),
+            nested_result(result, 2),
+        ))
+    }
+
+    /// Move the manager's settled balances on a pool back into the manager.
+    pub async fn withdraw_settled_amounts(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        proof: Argument,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "withdraw_settled_amounts",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![pool_arg, manager_arg, proof],
+        )
+    }
+
+    /// Withdraw `amount` (in human units) of one coin from the manager's settled balances
+    /// on a pool. All settled proceeds are moved into the manager, but only `amount` is
+    /// withdrawn; the rest stays there for further trading.
+    pub async fn withdraw_settled(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        coin_key: &str,
+        amount: f64,
+    ) -> Result<Argument> {
+        let manager_key = Some(self.manager_key(manager_key)?);
+        let pool = self.config.pool(pool_key)?;
+        let coin = self.config.coin(coin_key)?;
+        let coin_type = self.config.coin_type(coin_key)?;
+
+        let settled = self
+            .get_account(pool_key, manager_key)
+            .await?
+            .map(|account| account.settled_balances)
+            .unwrap_or_default();
+        let mut available = 0;
+        for (settled_coin, settled_amount) in [
+            (pool.base_coin.as_str(), settled.base),
+            (pool.quote_coin.as_str(), settled.quote),
+            ("DEEP", settled.deep),
+        ] {
+            if self.config.coin_type(settled_coin)? == coin_type {
+                available += settled_amount;
+            }
+        }
+        let units = coin.to_units(amount);
+        if units > available {
+            return Err(anyhow!(
+                "Cannot withdraw {} {} from settled balances on {}; only {} is settled",
+                amount,
+                coin_key,
+                pool_key,
+                coin.from_units(available)
+            ));
+        }
+
+        let proof = self.generate_proof(ptb, manager_key).await?;
+        self.withdraw_settled_amounts(ptb, pool_key, manager_key, proof)
+            .await?;
+        self.withdraw_released(ptb, manager_key, coin_key, units)
+            .await
+    }
+
+    /// Settle the manager's proceeds on every pool in `pool_keys` and withdraw them to
+    /// coins, one per coin type, all in the same PTB. Pools with nothing settled are skipped.
+    pub async fn withdraw_settled_all(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        pool_keys: &[&str],
+    ) -> Result<Vec<Argument>> {
+        let manager_key = Some(self.manager_key(manager_key)?);
+        let mut settled: BTreeMap<String, u64> = BTreeMap::new();
+        let mut shared_proof = None;
+        for pool_key in pool_keys {
+            let balances = match self.get_account(pool_key, manager_key).await? {
+                Some(account) if !account.settled_balances.is_empty() => account.settled_balances,
+                _ => continue,
+            };
+
+            // A TradeProof is valid for the whole PTB, so generate it once.
+            let proof = match shared_proof {
+                Some(proof) => proof,
+                None => *shared_proof.insert(self.generate_proof(ptb, manager_key).await?),
+            };
+            self.withdraw_settled_amounts(ptb, pool_key, manager_key, proof)
+                .await?;
+
+            let pool = self.config.pool(pool_key)?;
+            for (coin_key, amount) in [
+                (pool.base_coin.as_str(), balances.base),
+                (pool.quote_coin.as_str(), balances.quote),
+                ("DEEP", balances.deep),
+            ] {
+                if amount > 0 {
+                    *settled.entry(coin_key.to_string()).or_default() += amount;
+                }
+            }
+        }
+
+        let mut coins = Vec::with_capacity(settled.len());
+        for (coin_key, amount) in settled {
+            coins.push(
+                self.withdraw_released(ptb, manager_key, &coin_key, amount)
+                    .await?,
+            );
+        }
+        Ok(coins)
+    }
+
+    /// Stake `amount` DEEP on a pool straight from the DEEP already held by the manager,
+    /// without moving a coin out of the wallet. Fails locally if the manager holds less.
+    pub async fn stake_from_manager(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        amount: f64,
+    ) -> Result<Argument> {
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+
+        let units = deep.to_units(amount);
+        let available = self.get_manager_balance(Some(manager_key), "DEEP").await?;
+        let available_units = deep.to_units(available);
+        if units > available_units {
+            return Err(anyhow!(
+                "Cannot stake {} DEEP on {}: manager {} holds {} DEEP, {} short",
+                amount,
+                pool_key,
+                manager_key,
+                available,
+                deep.from_units(units - available_units)
+            ));
+        }
+
+        let proof = self.generate_proof(ptb, Some(manager_key)).await?;
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let amount_arg = ptb.pure(units)?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "stake",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![pool_arg, manager_arg, proof, amount_arg],
+        )
+    }
+
+    /// Propose new fee rates and stake requirement for a pool. Fees are rates, e.g.
+    /// 0.0005 for 5 bps, and `stake_required` is in DEEP. Fails locally if governance
+    /// would reject the fees; see `validate_proposal`.
+    pub async fn submit_proposal(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+        taker_fee: f64,
+        maker_fee: f64,
+        stake_required: f64,
+    ) -> Result<Argument> {
+        self.validate_proposal(pool_key, taker_fee, maker_fee)
+            .await?;
+        let manager_key = self.manager_key(manager_key)?;
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+
+        let proof = self.generate_proof(ptb, Some(manager_key)).await?;
+        let pool_arg = ptb.obj(self.ctx.shared_object(pool.id()?, true).await?)?;
+        let manager_arg = ptb.obj(
+            self.ctx
+                .shared_object(self.balance_manager_id(manager_key)?, true)
+                .await?,
+        )?;
+        let taker_fee = ptb.pure((taker_fee * FLOAT_SCALAR).round() as u64)?;
+        let maker_fee = ptb.pure((maker_fee * FLOAT_SCALAR).round() as u64)?;
+        let stake_required = ptb.pure(deep.to_units(stake_required))?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "submit_proposal",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![
+                pool_arg,
+                manager_arg,
+                proof,
+                taker_fee,
+                maker_fee,
+                stake_required,
+            ],
+        )
+    }
+
+    /// Create a pool of `base_coin` against `quote_coin`, paying the protocol's creation
+    /// fee in DEEP from the sender's coins. `tick_size` is a price and `lot_size` and
+    /// `min_size` are base quantities, all in human units. Returns the new pool's ID.
+    pub async fn create_permissionless_pool(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        base_coin: &str,
+        quote_coin: &str,
+        tick_size: f64,
+        lot_size: f64,
+        min_size: f64,
+    ) -> Result<Argument> {
+        self.validate_pool_creation(base_coin, quote_coin, tick_size, lot_size, min_size)?;
+        let base = self.config.coin(base_coin)?;
+        let quote = self.config.coin(quote_coin)?;
+        let deep = self.config.coin("DEEP")?;
+
+        let creation_fee = self.get_protocol_config().await?.pool_creation_fee;
+        if self.balance_checks {
+            self.ensure_balance("DEEP", creation_fee, BalanceSource::Wallet)
+                .await?;
+        }
+        let registry = ptb.obj(
+            self.ctx
+                .shared_object(self.config.registry_id()?, true)
+                .await?,
+        )?;
+        let tick_size = ptb.pure(price_to_units(tick_size, base, quote))?;
+        let lot_size = ptb.pure(base.to_units(lot_size))?;
+        let min_size = ptb.pure(base.to_units(min_size))?;
+        let fee_coin = self
+            .ctx
+            .coin_with_balance(ptb, self.sender, deep, deep.to_units(creation_fee))
+            .await?;
+
+        move_call(
+            ptb,
+            self.config.package_id()?,
+            "pool",
+            "create_permissionless_pool",
+            vec![base.type_tag()?, quote.type_tag()?],
+            vec![registry, tick_size, lot_size, min_size, fee_coin],
+        )
+    }
+
+    /// Leave a pool: cancel every open order, settle the manager's proceeds and withdraw
+    /// both the released and the settled funds to coins, one per coin type, in one PTB.
+    /// Returns no coins if the manager never traded on the pool.
+    ///
+    /// Only funds freed from this pool are withdrawn; the rest of the manager's balance
+    /// stays where it is.
+    pub async fn exit_pool(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        pool_key: &str,
+        manager_key: Option<&str>,
+    ) -> Result<Vec<Argument>> {
+        let manager_key = Some(self.manager_key(manager_key)?);
+        let Some(account) = self.get_account(pool_key, manager_key).await? else {
+            return Ok(vec![]);
+        };
+        let pool = self.config.pool(pool_key)?;
+
+        // `pool::locked_balance` already counts the settled balances, so it alone is
+        // what the cancel and settle below release.
+        let has_open_orders = !account.open_orders.contents.is_empty();
+        let freed = if has_open_orders {
+            self.get_locked_balance_units(pool_key, manager_key).await?
+        } else {
+            account.settled_balances
+        };
+        let mut released: BTreeMap<String, u64> = BTreeMap::new();
+        for (coin_key, amount) in [
+            (pool.base_coin.as_str(), freed.base),
+            (pool.quote_coin.as_str(), freed.quote),
+            ("DEEP", freed.deep),
+        ] {
+            if amount > 0 {
+                *released.entry(coin_key.to_string()).or_default() += amount;
+            }
+        }
+
+        let proof = self.generate_proof(ptb, manager_key).await?;
+        if has_open_orders {
+            self.cancel_all_orders_with_proof(ptb, pool_key, manager_key, proof)
+                .await?;
+        }
+        self.withdraw_settled_amounts(ptb, pool_key, manager_key, proof)
+            .await?;
+
+        let mut coins = Vec::with_capacity(released.len());
+        for (coin_key, amount) in released {
+            coins.push(
+                self.withdraw_released(ptb, manager_key, &coin_key, amount)
+                    .await?,
+            );
+        }
+        Ok(coins)
+    }
+
+    /// Sweep settled proceeds across `pool_keys`, skipping coins whose total settled
+    /// amount is below `min_threshold` (in each coin's human units) so gas is not spent
+    /// on dust. Only pools holding a swept coin are settled.
+    ///
+    /// With `keep_in_manager` the swept funds stay consolidated in the manager's free
+    /// balance; otherwise they are withdrawn to one coin per type for the caller to
+    /// transfer. Dust stays in the pools' settled balances, or in the manager when it
+    /// shares a pool with a swept coin.
+    pub async fn sweep_dust(
+        &self,
+        ptb: &mut ProgrammableTransactionBuilder,
+        manager_key: Option<&str>,
+        pool_keys: &[&str],
+        min_threshold: f64,
+        keep_in_manager: bool,
+    ) -> Result<SweepReport> {
+        let manager_key = Some(self.manager_key(manager_key)?);
+        let mut settled_by_pool = vec![];
+        let mut totals: BTreeMap<String, u64> = BTreeMap::new();
+        for pool_key in pool_keys {
+            let Some(account) = self.get_account(pool_key, manager_key).await? else {
+                continue;
+            };
+            let balances = account.settled_balances;
+            let pool = self.config.pool(pool_key)?;
+            let amounts = [
+                (pool.base_coin.clone(), balances.base),
+                (pool.quote_coin.clone(), balances.quote),
+                ("DEEP".to_string(), balances.deep),
+            ];
+            for (coin_key, amount) in &amounts {
+                if *amount > 0 {
+                    *totals.entry(coin_key.clone()).or_default() += amount;
+                }
+            }
+            settled_by_pool.push((pool_key, amounts));
+        }
+
+        let mut report = SweepReport::default();
+        for (coin_key, amount) in &totals {
+            let coin = self.config.coin(coin_key)?;
+            let amount = coin.from_units(*amount);
+            if amount >= min_threshold {
+                report.swept.insert(coin_key.clone(), amount);
+            } else {
+                report.dust.insert(coin_key.clone(), amount);
+            }
+        }
+
+        let mut shared_proof = None;
+        for (pool_key, amounts) in settled_by_pool {
+            let holds_swept = amounts
+                .iter()
+                .any(|(coin_key, amount)| *amount > 0 && report.swept.contains_key(coin_key));
+            if !holds_swept {
+                continue;
+            }
+            let proof = match shared_proof {
+                Some(proof) => proof,
+                None => *shared_proof.insert(self.generate_proof(ptb, manager_key).await?),
+            };
+            self.withdraw_settled_amounts(ptb, pool_key, manager_key, proof)
+                .await?;
+        }
+
+        if !keep_in_manager {
+            for coin_key in report.swept.keys() {
+                let coin = self
+                    .withdraw_released(ptb, manager_key, coin_key, totals[coin_key])
+                    .await?;
+                report.coins.push(coin);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Whitelisted pools charge no fees, so paying them in the input asset is meaningless there.
+    pub(crate) async fn ensure_fee_option(
+        &self,
+        pool_key: &str,
+        pay_with_deep: bool,
+    ) -> Result<()> {
+        if !pay_with_deep {
+            check_fee_option(pool_key, pay_with_deep, self.whitelisted(pool_key).await?)?;
+        }
+        Ok(())
+    }
+
+    /// DEEP to hand to a swap: none on whitelisted pools or when paying fees in the
+    /// input coin, otherwise the supplied amount once it covers the estimated fee.
+    /// With `exact_fee`, the estimated fee itself is used in place of `deep_amount`.
+    async fn swap_deep_units(
+        &self,
+        params: &SwapParams,
+        base_in: bool,
+        exact_fee: bool,
+    ) -> Result<u64> {
+        let whitelisted = self.whitelisted(&params.pool_key).await?;
+        check_fee_option(&params.pool_key, params.pay_with_deep, whitelisted)?;
+        if whitelisted || !params.pay_with_deep {
+            return Ok(0);
+        }
+
+        let estimate = if base_in {
+            self.get_quote_quantity_out(&params.pool_key, params.amount)
+                .await?
+        } else {
+            self.get_base_quantity_out(&params.pool_key, params.amount)
+                .await?
+        };
+        let deep = self.config.coin("DEEP")?;
+        if exact_fee {
+            return Ok(deep.to_units(estimate.deep_required));
+        }
+        if params.deep_amount < estimate.deep_required {
+            return Err(anyhow!(
+                "Swap on {} requires at least {} DEEP for fees, but deep_amount is {}",
+                params.pool_key,
+                estimate.deep_required,
+                params.deep_amount
+            ));
+        }
+        Ok(deep.to_units(params.deep_amount))
+    }
+}
+
+fn check_fee_option(pool_key: &str, pay_with_deep: bool, whitelisted: bool) -> Result<()> {
+    if !pay_with_deep && whitelisted {
+        return Err(anyhow!(
+            "Pool {} is whitelisted; pay_with_deep = false is only supported on non-whitelisted pools",
+            pool_key
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_sdk::types::{
+        Identifier, SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION,
+        base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress},
+        collection_types::VecSet,
+        digests::ObjectDigest,
+        transaction::{CallArg, Command, ObjectArg, ProgrammableTransaction},
+    };
+
+    use super::*;
+    use crate::client::test_support::{MANAGER_ID, move_call_names, offline_client};
+    use crate::config::DeepBookConfig;
+    use crate::context::{BuildContext, command_snapshot};
+    use crate::types::{Account, Balances, Order, OrderDeepPrice, PoolBookParams};
+
+    fn pool_id(pool_key: &str) -> ObjectID {
+        let config = DeepBookConfig::new("testnet").unwrap();
+        config.pool(pool_key).unwrap().id().unwrap()
+    }
+
+    fn dbusdc_coin(byte: u8) -> ObjectRef {
+        (
+            ObjectID::from_single_byte(byte),
+            SequenceNumber::from_u64(1),
+            ObjectDigest::MIN,
+        )
+    }
+
+    /// Stubs for swapping on SUI_DBUSDC, a non-whitelisted pool, from two DBUSDC coins.
+    fn swap_context() -> BuildContext {
+        let config = DeepBookConfig::new("testnet").unwrap();
+        let dbusdc = config.coin("DBUSDC").unwrap();
+        BuildContext::fixed()
+            .with_whitelisted(pool_id("SUI_DBUSDC"), false)
+            .with_coins(&dbusdc.type_, vec![dbusdc_coin(1), dbusdc_coin(2)])
+    }
+
+    /// Swap 10 DBUSDC for SUI, paying the fee in DBUSDC, and send every output back.
+    async fn build_swap(client: &TradingClient) -> Result<ProgrammableTransaction> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let params = SwapParams {
+            pool_key: "SUI_DBUSDC".to_string(),
+            amount: 10.0,
+            min_out: 2.0,
+            pay_with_deep: false,
+            ..Default::default()
+        };
+        let (base, quote, deep) = client.swap_exact_quote_for_base(&mut ptb, &params).await?;
+        ptb.transfer_args(client.sender, vec![base, quote, deep]);
+        Ok(ptb.finish())
+    }
+
+    #[tokio::test]
+    async fn swap_command_snapshot() {
+        let client = offline_client(swap_context());
+        let pt = build_swap(&client).await.unwrap();
+
+        let config = &client.config;
+        let type_tag = |coin_key: &str| config.coin(coin_key).unwrap().type_tag().unwrap();
+        let ident = |name: &str| Identifier::new(name).unwrap();
+        let expected = ProgrammableTransaction {
+            inputs: vec![
+                CallArg::Pure(bcs::to_bytes(&10_000_000u64).unwrap()),
+                CallArg::Object(ObjectArg::ImmOrOwnedObject(dbusdc_coin(1))),
+                CallArg::Object(ObjectArg::ImmOrOwnedObject(dbusdc_coin(2))),
+                CallArg::Object(ObjectArg::SharedObject {
+                    id: pool_id("SUI_DBUSDC"),
+                    initial_shared_version: SequenceNumber::from_u64(1),
+                    mutable: true,
+                }),
+                CallArg::Pure(bcs::to_bytes(&2_000_000_000u64).unwrap()),
+                CallArg::Object(ObjectArg::SharedObject {
+                    id: SUI_CLOCK_OBJECT_ID,
+                    initial_shared_version: SUI_CLOCK_OBJECT_SHARED_VERSION,
+                    mutable: false,
+                }),
+                CallArg::Pure(bcs::to_bytes(&SuiAddress::ZERO).unwrap()),
+            ],
+            commands: vec![
+                // Merge the two DBUSDC coins and split off the 10 DBUSDC input.
+                Command::MergeCoins(Argument::Input(1), vec![Argument::Input(2)]),
+                Command::SplitCoins(Argument::Input(1), vec![Argument::Input(0)]),
+                // No DEEP: the fee comes out of the input coin.
+                Command::move_call(
+                    SUI_FRAMEWORK_PACKAGE_ID,
+                    ident("coin"),
+                    ident("zero"),
+                    vec![type_tag("DEEP")],
+                    vec![],
+                ),
+                Command::move_call(
+                    config.package_id().unwrap(),
+                    ident("pool"),
+                    ident("swap_exact_quote_for_base"),
+                    vec![type_tag("SUI"), type_tag("DBUSDC")],
+                    vec![
+                        Argument::Input(3),
+                        Argument::NestedResult(1, 0),
+                        Argument::Result(2),
+                        Argument::Input(4),
+                        Argument::Input(5),
+                    ],
+                ),
+                Command::TransferObjects(
+                    vec![
+                        Argument::NestedResult(3, 0),
+                        Argument::NestedResult(3, 1),
+                        Argument::NestedResult(3, 2),
+                    ],
+                    Argument::Input(6),
+                ),
+            ],
+        };
+        assert_eq!(command_snapshot(&pt), command_snapshot(&expected));
+        assert_eq!(pt.inputs, expected.inputs);
+    }
+
+    /// A 1 SUI bid at 2 DBUSDC on SUI_DBUSDC, fitting the book params below.
+    fn sui_bid(pay_with_deep: bool) -> PlaceLimitOrderParams {
+        PlaceLimitOrderParams {
+            pool_key: "SUI_DBUSDC".to_string(),
+            price: 2.0,
+            quantity: 1.0,
+            side: Side::Bid,
+            pay_with_deep,
+            ..Default::default()
+        }
+    }
+
+    fn order_context(whitelisted: bool) -> BuildContext {
+        BuildContext::fixed()
+            .with_whitelisted(pool_id("SUI_DBUSDC"), whitelisted)
+            .with_book_params(
+                pool_id("SUI_DBUSDC"),
+                PoolBookParams {
+                    tick_size: 1_000,
+                    lot_size: 1_000_000,
+                    min_size: 10_000_000,
+                },
+            )
+    }
+
+    #[tokio::test]
+    async fn swap_without_deep_passes_a_zero_deep_coin() {
+        let pt = build_swap(&offline_client(swap_context())).await.unwrap();
+        let Command::MoveCall(zero) = &pt.commands[2] else {
+            panic!("expected coin::zero, got {:?}", pt.commands[2]);
+        };
+        assert_eq!(zero.function.as_str(), "zero");
+        assert!(format!("{:?}", zero.type_arguments).contains("DEEP"));
+        let Command::MoveCall(swap) = &pt.commands[3] else {
+            panic!("expected the swap, got {:?}", pt.commands[3]);
+        };
+        assert_eq!(swap.arguments[2], Argument::Result(2));
+    }
+
+    #[tokio::test]
+    async fn limit_order_without_deep_passes_false() {
+        let client = offline_client(order_context(false));
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        client
+            .place_limit_order(&mut ptb, &sui_bid(false))
+            .await
+            .unwrap();
+        let pt = ptb.finish();
+        let Some(Command::MoveCall(order)) = pt.commands.last() else {
+            panic!("expected the order, got {:?}", pt.commands.last());
+        };
+        assert_eq!(order.function.as_str(), "place_limit_order");
+        let Argument::Input(pay_with_deep) = order.arguments[9] else {
+            panic!("pay_with_deep is not an input: {:?}", order.arguments[9]);
+        };
+        assert_eq!(
+            pt.inputs[pay_with_deep as usize],
+            CallArg::Pure(bcs::to_bytes(&false).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn whitelisted_pools_reject_fees_outside_deep() {
+        let client = offline_client(order_context(true));
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        assert!(
+            client
+                .place_limit_order(&mut ptb, &sui_bid(false))
+                .await
+                .is_err()
+        );
+        let client = offline_client(swap_context().with_whitelisted(pool_id("SUI_DBUSDC"), true));
+        assert!(build_swap(&client).await.is_err());
+    }
+
+    /// The amount each `balance_manager::withdraw` producing one of `coins` takes out.
+    fn withdrawn(pt: &ProgrammableTransaction, coins: &[Argument]) -> Vec<u64> {
+        coins
+            .iter()
+            .map(|coin| {
+                let Argument::Result(index) = coin else {
+                    panic!("expected a withdrawal, got {:?}", coin);
+                };
+                let command = &pt.commands[*index as usize];
+                let Command::MoveCall(withdraw) = command else {
+                    panic!("expected a withdrawal, got {:?}", command);
+                };
+                assert_eq!(withdraw.function.as_str(), "withdraw");
+                let Argument::Input(input) = withdraw.arguments[1] else {
+                    panic!("amount is not an input: {:?}", withdraw.arguments[1]);
+                };
+                let CallArg::Pure(amount) = &pt.inputs[input as usize] else {
+                    panic!(
+                        "amount is not a pure input: {:?}",
+                        pt.inputs[input as usize]
+                    );
+                };
+                bcs::from_bytes(amount).unwrap()
+            })
+            .collect()
+    }
+
+    fn settled_account(settled_balances: Balances) -> Account {
+        Account {
+            epoch: 1,
+            open_orders: VecSet { contents: vec![] },
+            taker_volume: 0,
+            maker_volume: 0,
+            active_stake: 0,
+            inactive_stake: 0,
+            created_proposal: false,
+            voted_proposal: None,
+            unclaimed_rebates: Balances::default(),
+            settled_balances,
+            owed_balances: Balances::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn withdraw_settled_all_merges_proceeds_across_pools() {
+        let manager_id = ObjectID::from_hex_literal(MANAGER_ID).unwrap();
+        let ctx = BuildContext::fixed()
+            .with_account(
+                pool_id("SUI_DBUSDC"),
+                manager_id,
+                Some(settled_account(Balances {
+                    base: 5,
+                    quote: 0,
+                    deep: 0,
+                })),
+            )
+            .with_account(
+                pool_id("DEEP_SUI"),
+                manager_id,
+                Some(settled_account(Balances {
+                    base: 3,
+                    quote: 7,
+                    deep: 4,
+                })),
+            )
+            .with_account(
+                pool_id("DEEP_DBUSDC"),
+                manager_id,
+                Some(settled_account(Balances::default())),
+            )
+            .with_account(pool_id("DBUSDT_DBUSDC"), manager_id, None);
+        let client = offline_client(ctx);
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let coins = client
+            .withdraw_settled_all(
+                &mut ptb,
+                None,
+                &["SUI_DBUSDC", "DEEP_SUI", "DEEP_DBUSDC", "DBUSDT_DBUSDC"],
+            )
+            .await
+            .unwrap();
+        ptb.transfer_args(client.sender, coins.clone());
+        let pt = ptb.finish();
+
+        // One proof, a settle per pool with proceeds, and one withdrawal per coin type.
+        assert_eq!(coins.len(), 2);
+        assert_eq!(
+            move_call_names(&pt),
+            [
+                "balance_manager::generate_proof_as_owner",
+                "pool::withdraw_settled_amounts",
+                "pool::withdraw_settled_amounts",
+                "balance_manager::withdraw",
+                "balance_manager::withdraw",
+            ]
+        );
+        // DEEP (3 base on DEEP_SUI plus 4 in fees), then SUI (5 + 7).
+        assert_eq!(withdrawn(&pt, &coins), [7, 12]);
+    }
+
+    #[tokio::test]
+    async fn extend_expiration_replaces_the_order() {
+        let manager_id = ObjectID::from_hex_literal(MANAGER_ID).unwrap();
+        // A bid at 2 DBUSDC with 1 of its 1.5 SUI still resting.
+        let order = Order {
+            balance_manager_id: manager_id,
+            order_id: (2_000_000u128 << 64) | 42,
+            client_order_id: 7,
+            quantity: 1_500_000_000,
+            filled_quantity: 500_000_000,
+            fee_is_deep: false,
+            order_deep_price: OrderDeepPrice {
+                asset_is_base: true,
+                deep_per_asset: 0,
+            },
+            epoch: 1,
+            status: 0,
+            expire_timestamp: 2_000,
+        };
+        let client = offline_client(
+            order_context(false)
+                .with_clock(1_000)
+                .with_order(pool_id("SUI_DBUSDC"), order.clone()),
+        );
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        assert!(
+            client
+                .extend_expiration(&mut ptb, "SUI_DBUSDC", None, order.order_id, 1_000, None)
+                .await
+                .is_err()
+        );
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        client
+            .extend_expiration(
+                &mut ptb,
+                "SUI_DBUSDC",
+                None,
+                order.order_id,
+                90_000,
+                Some(OrderType::PostOnly),
+            )
+            .await
+            .unwrap();
+        let pt = ptb.finish();
+
+        assert_eq!(
+            move_call_names(&pt),
+            [
+                "balance_manager::generate_proof_as_owner",
+                "pool::cancel_order",
+                "pool::place_limit_order",
+            ]
+        );
+        let Some(Command::MoveCall(place)) = pt.commands.last() else {
+            panic!("expected the replacement, got {:?}", pt.commands.last());
+        };
+        let pure = |index: usize| match place.arguments[index] {
+            Argument::Input(input) => pt.inputs[input as usize].clone(),
+            other => panic!("argument {} is not an input: {:?}", index, other),
+        };
+        for (index, value) in [
+            (3, bcs::to_bytes(&7u64)),
+            (4, bcs::to_bytes(&(OrderType::PostOnly as u8))),
+            (6, bcs::to_bytes(&2_000_000u64)),
+            (7, bcs::to_bytes(&1_000_000_000u64)),
+            (8, bcs::to_bytes(&true)),
+            (9, bcs::to_bytes(&false)),
+            (10, bcs::to_bytes(&90_000u64)),
+        ] {
+            assert_eq!(
+                pure(index),
+                CallArg::Pure(value.unwrap()),
+                "argument {}",
+                index
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn extend_expiration_rejects_foreign_and_dust_orders() {
+        let resting = |balance_manager_id, order_id, filled_quantity| Order {
+            balance_manager_id,
+            order_id,
+            client_order_id: 7,
+            quantity: 1_500_000_000,
+            filled_quantity,
+            fee_is_deep: false,
+            order_deep_price: OrderDeepPrice {
+                asset_is_base: true,
+                deep_per_asset: 0,
+            },
+            epoch: 1,
+            status: 0,
+            expire_timestamp: 2_000,
+        };
+        let manager_id = ObjectID::from_hex_literal(MANAGER_ID).unwrap();
+        let foreign = resting(
+            ObjectID::from_single_byte(0xee),
+            (2_000_000u128 << 64) | 1,
+            0,
+        );
+        // 0.005 SUI left, under the 0.01 SUI minimum size.
+        let dust = resting(manager_id, (2_000_000u128 << 64) | 2, 1_495_000_000);
+        let client = offline_client(
+            order_context(false)
+                .with_clock(1_000)
+                .with_order(pool_id("SUI_DBUSDC"), foreign.clone())
+                .with_order(pool_id("SUI_DBUSDC"), dust.clone()),
+        );
+
+        for order_id in [foreign.order_id, dust.order_id] {
+            let mut ptb = ProgrammableTransactionBuilder::new();
+            assert!(
+                client
+                    .extend_expiration(&mut ptb, "SUI_DBUSDC", None, order_id, 90_000, None)
+                    .await
+                    .is_err()
+            );
+            assert!(ptb.finish().commands.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn create_pool_rejects_invalid_sizes_before_building() {
+        let client = offline_client(BuildContext::fixed());
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        // 0.0015 SUI is 1_500_000 units, not a power of ten.
+        let error = client
+            .create_permissionless_pool(&mut ptb, "SUI", "DBUSDC", 0.001, 0.0015, 0.01)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Lot size"));
+        assert!(ptb.finish().commands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn exit_pool_withdraws_the_locked_balance_once() {
+        let manager_id = ObjectID::from_hex_literal(MANAGER_ID).unwrap();
+        let settled = Balances {
+            base: 3,
+            quote: 7,
+            deep: 4,
+        };
+        let account = Account {
+            open_orders: VecSet {
+                contents: vec![(2_000_000u128 << 64) | 42],
+            },
+            ..settled_account(settled)
+        };
+        // `pool::locked_balance` reports open orders plus the settled balances above.
+        let locked = Balances {
+            base: 100 + settled.base,
+            quote: 50 + settled.quote,
+            deep: 5 + settled.deep,
+        };
+        let client = offline_client(
+            BuildContext::fixed()
+                .with_account(pool_id("DEEP_SUI"), manager_id, Some(account))
+                .with_locked_balance(pool_id("DEEP_SUI"), manager_id, locked),
+        );
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let coins = client.exit_pool(&mut ptb, "DEEP_SUI", None).await.unwrap();
+        ptb.transfer_args(client.sender, coins.clone());
+        let pt = ptb.finish();
+
+        assert_eq!(
+            move_call_names(&pt),
+            [
+                "balance_manager::generate_proof_as_owner",
+                "pool::cancel_all_orders",
+                "pool::withdraw_settled_amounts",
+                "balance_manager::withdraw",
+                "balance_manager::withdraw",
+            ]
+        );
+        // DEEP is the base coin and the fee coin: 103 + 9, then 57 SUI.
+        assert_eq!(withdrawn(&pt, &coins), [112, 57]);
+    }
+}
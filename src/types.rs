@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde_derive::Deserialize;
+use sui_sdk::types::{base_types::ObjectID, transaction::Argument};
+use sui_types::collection_types::VecSet;
+
+use crate::config::{Coin, DeepBookConfig, parse_price_units, price_from_units, price_to_units};
+
+/// Expiration used by DeepBook for good-till-cancelled orders.
+pub const MAX_TIMESTAMP: u64 = 1_844_674_407_370_955_161;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    NoRestriction = 0,
+    ImmediateOrCancel = 1,
+    FillOrKill = 2,
+    PostOnly = 3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfMatchingOptions {
+    SelfMatchingAllowed = 0,
+    CancelTaker = 1,
+    CancelMaker = 2,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Side {
+    #[default]
+    Bid,
+    Ask,
+}
+
+impl Side {
+    pub fn is_bid(self) -> bool {
+        self == Side::Bid
+    }
+
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        }
+    }
+}
+
+/// Which way to snap a price or quantity onto the pool's tick or lot grid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    Floor,
+    Ceil,
+    #[default]
+    Nearest,
+}
+
+/// Where `ensure_balance` looks for funds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceSource {
+    /// Coins owned by the client's sender.
+    Wallet,
+    /// Free balance of the balance manager with this key.
+    Manager(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaceLimitOrderParams {
+    pub pool_key: String,
+    /// Falls back to the client's default manager when `None`.
+    pub balance_manager_key: Option<String>,
+    pub client_order_id: u64,
+    pub price: f64,
+    pub quantity: f64,
+    /// Exact on-chain price and base quantity; take precedence over `price` and
+    /// `quantity` when set. Filled in by `from_decimal_strs`.
+    pub price_units: Option<u64>,
+    pub quantity_units: Option<u64>,
+    pub side: Side,
+    pub expiration: Option<u64>,
+    pub order_type: Option<OrderType>,
+    pub self_matching_option: Option<SelfMatchingOptions>,
+    /// Pay fees in DEEP; when false the fee is taken from the traded asset.
+    pub pay_with_deep: bool,
+}
+
+impl Default for PlaceLimitOrderParams {
+    fn default() -> Self {
+        PlaceLimitOrderParams {
+            pool_key: String::new(),
+            balance_manager_key: None,
+            client_order_id: 0,
+            price: 0.0,
+            quantity: 0.0,
+            price_units: None,
+            quantity_units: None,
+            side: Side::Bid,
+            expiration: None,
+            order_type: None,
+            self_matching_option: None,
+            pay_with_deep: true,
+        }
+    }
+}
+
+impl PlaceLimitOrderParams {
+    /// Build params from decimal strings, parsed exactly against the pool's coin
+    /// decimals. Strings with more precision than the coins support are rejected.
+    pub fn from_decimal_strs(
+        config: &DeepBookConfig,
+        pool_key: &str,
+        price: &str,
+        quantity: &str,
+        side: Side,
+    ) -> Result<Self> {
+        let pool = config.pool(pool_key)?;
+        let base = config.coin(&pool.base_coin)?;
+        let quote = config.coin(&pool.quote_coin)?;
+        let price_units = parse_price_units(price, base, quote)?;
+        let quantity_units = base.parse_units(quantity)?;
+        Ok(PlaceLimitOrderParams {
+            pool_key: pool_key.to_string(),
+            price: price_from_units(price_units, base, quote),
+            quantity: base.from_units(quantity_units),
+            price_units: Some(price_units),
+            quantity_units: Some(quantity_units),
+            side,
+            ..Default::default()
+        })
+    }
+
+    pub fn price_units(&self, base: &Coin, quote: &Coin) -> u64 {
+        self.price_units
+            .unwrap_or_else(|| price_to_units(self.price, base, quote))
+    }
+
+    pub fn quantity_units(&self, base: &Coin) -> u64 {
+        self.quantity_units
+            .unwrap_or_else(|| base.to_units(self.quantity))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaceMarketOrderParams {
+    pub pool_key: String,
+    /// Falls back to the client's default manager when `None`.
+    pub balance_manager_key: Option<String>,
+    pub client_order_id: u64,
+    pub quantity: f64,
+    /// Exact base quantity; takes precedence over `quantity` when set.
+    pub quantity_units: Option<u64>,
+    pub side: Side,
+    pub self_matching_option: Option<SelfMatchingOptions>,
+    /// Pay fees in DEEP; when false the fee is taken from the traded asset.
+    pub pay_with_deep: bool,
+}
+
+impl Default for PlaceMarketOrderParams {
+    fn default() -> Self {
+        PlaceMarketOrderParams {
+            pool_key: String::new(),
+            balance_manager_key: None,
+            client_order_id: 0,
+            quantity: 0.0,
+            quantity_units: None,
+            side: Side::Bid,
+            self_matching_option: None,
+            pay_with_deep: true,
+        }
+    }
+}
+
+impl PlaceMarketOrderParams {
+    /// Build params from a decimal quantity string, parsed exactly against the base coin.
+    pub fn from_decimal_str(
+        config: &DeepBookConfig,
+        pool_key: &str,
+        quantity: &str,
+        side: Side,
+    ) -> Result<Self> {
+        let base = config.coin(&config.pool(pool_key)?.base_coin)?;
+        let quantity_units = base.parse_units(quantity)?;
+        Ok(PlaceMarketOrderParams {
+            pool_key: pool_key.to_string(),
+            quantity: base.from_units(quantity_units),
+            quantity_units: Some(quantity_units),
+            side,
+            ..Default::default()
+        })
+    }
+
+    pub fn quantity_units(&self, base: &Coin) -> u64 {
+        self.quantity_units
+            .unwrap_or_else(|| base.to_units(self.quantity))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SwapParams {
+    pub pool_key: String,
+    pub amount: f64,
+    pub deep_amount: f64,
+    pub min_out: f64,
+    /// Pay fees in DEEP; when false `deep_amount` is ignored and the fee is taken
+    /// from the input coin.
+    pub pay_with_deep: bool,
+}
+
+impl Default for SwapParams {
+    fn default() -> Self {
+        SwapParams {
+            pool_key: String::new(),
+            amount: 0.0,
+            deep_amount: 0.0,
+            min_out: 0.0,
+            pay_with_deep: true,
+        }
+    }
+}
+
+/// Tick, lot and minimum order size of a pool, in on-chain units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolBookParams {
+    pub tick_size: u64,
+    pub lot_size: u64,
+    pub min_size: u64,
+}
+
+/// Outcome of `sweep_dust`, per coin key in human units.
+#[derive(Debug, Clone, Default)]
+pub struct SweepReport {
+    /// Coins whose settled total met the threshold and were swept.
+    pub swept: BTreeMap<String, f64>,
+    /// Coins below the threshold, left where they were.
+    pub dust: BTreeMap<String, f64>,
+    /// Withdrawn coins, one per swept coin type; empty when swept into the manager.
+    pub coins: Vec<Argument>,
+}
+
+/// The two orders of a quote placed by `place_two_sided_quote`, as the `OrderInfo`
+/// results of their `place_limit_order` calls.
+#[derive(Debug, Clone, Copy)]
+pub struct TwoSidedQuote {
+    pub bid: Argument,
+    pub ask: Argument,
+}
+
+/// Order IDs of an executed two-sided quote. An ID is `None` if no event for that
+/// side was found in the response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedQuote {
+    pub digest: String,
+    pub bid_order_id: Option<u128>,
+    pub ask_order_id: Option<u128>,
+}
+
+/// Objects a transaction created, as (object ID, full Move type) pairs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreatedObjects {
+    pub digest: String,
+    pub objects: Vec<(ObjectID, String)>,
+}
+
+impl CreatedObjects {
+    /// The first created object whose type, without type arguments, ends with
+    /// `type_filter`, e.g. "balance_manager::BalanceManager" or "pool::Pool".
+    pub fn created_object_of_type(&self, type_filter: &str) -> Option<ObjectID> {
+        self.objects
+            .iter()
+            .find(|(_, type_)| {
+                type_ == type_filter
+                    || type_
+                        .split('<')
+                        .next()
+                        .is_some_and(|name| name.ends_with(type_filter))
+            })
+            .map(|(id, _)| *id)
+    }
+}
+
+/// Taker and maker fee rates (scaled by `FLOAT_SCALAR`) and the DEEP stake needed for
+/// reduced fees, in on-chain units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolTradeParams {
+    pub taker_fee: u64,
+    pub maker_fee: u64,
+    pub stake_required: u64,
+}
+
+/// Everything about a pool in one place; sizes are in human units, fees as fractions.
+#[derive(Debug, Clone)]
+pub struct FullPoolInfo {
+    pub pool_key: String,
+    pub pool_id: ObjectID,
+    pub base_coin: String,
+    pub quote_coin: String,
+    pub base_type: String,
+    pub quote_type: String,
+    pub base_decimals: u32,
+    pub quote_decimals: u32,
+    pub tick_size: f64,
+    pub lot_size: f64,
+    pub min_size: f64,
+    pub taker_fee: f64,
+    pub maker_fee: f64,
+    /// DEEP stake needed for the reduced taker fee.
+    pub stake_required: f64,
+    pub whitelisted: bool,
+    pub book_params: PoolBookParams,
+    pub trade_params: PoolTradeParams,
+}
+
+/// Result of a swap quote, in human units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantityOut {
+    pub base_out: f64,
+    pub quote_out: f64,
+    pub deep_required: f64,
+}
+
+/// What an executed swap did to the sender's balances, in human units.
+///
+/// `base_received` and `quote_received` are net changes, so the coin swapped in shows
+/// up as a negative amount. When DEEP is the pool's base or quote coin its fee is
+/// folded into that side and `deep_spent` is zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SwapResult {
+    pub base_received: f64,
+    pub quote_received: f64,
+    pub deep_spent: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Balances {
+    pub base: u64,
+    pub quote: u64,
+    pub deep: u64,
+}
+
+impl Balances {
+    pub fn is_empty(&self) -> bool {
+        self.base == 0 && self.quote == 0 && self.deep == 0
+    }
+}
+
+/// A balance manager's account on a pool, as returned by `pool::account`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Account {
+    pub epoch: u64,
+    pub open_orders: VecSet<u128>,
+    pub taker_volume: u128,
+    pub maker_volume: u128,
+    pub active_stake: u64,
+    pub inactive_stake: u64,
+    pub created_proposal: bool,
+    pub voted_proposal: Option<ObjectID>,
+    pub unclaimed_rebates: Balances,
+    pub settled_balances: Balances,
+    pub owed_balances: Balances,
+}
+
+/// How a limit order would interact with the book if placed now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRole {
+    /// Does not reach the opposite side and would rest on the book.
+    Maker,
+    /// Crosses through the opposite best price and takes liquidity.
+    Taker,
+    /// Priced exactly at the opposite best; matches against the top level only.
+    Crossing,
+}
+
+/// One aggregated price level of the book, in human units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    pub price: f64,
+    pub quantity: f64,
+    /// DeepBook's level2 accessors aggregate quantity by price without reporting how
+    /// many orders make up the level, so this is `None` for levels read from level2.
+    pub order_count: Option<u64>,
+}
+
+/// Bids best-first (descending price) and asks best-first (ascending price).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderBook {
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+/// Aggregate book statistics in human units; best prices are `None` for an empty side.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BookSummary {
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+    pub total_bid_quantity: f64,
+    pub total_ask_quantity: f64,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct OrderDeepPrice {
+    pub asset_is_base: bool,
+    pub deep_per_asset: u64,
+}
+
+/// A resting order, as returned by `pool::get_order`; quantities are in on-chain units.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Order {
+    pub balance_manager_id: ObjectID,
+    pub order_id: u128,
+    pub client_order_id: u64,
+    pub quantity: u64,
+    pub filled_quantity: u64,
+    pub fee_is_deep: bool,
+    pub order_deep_price: OrderDeepPrice,
+    pub epoch: u64,
+    pub status: u8,
+    pub expire_timestamp: u64,
+}
+
+impl Order {
+    pub fn side(&self) -> Side {
+        decode_order_id(self.order_id).0
+    }
+
+    /// On-chain price encoded in the order ID.
+    pub fn price(&self) -> u64 {
+        decode_order_id(self.order_id).1
+    }
+
+    pub fn remaining_quantity(&self) -> u64 {
+        self.quantity - self.filled_quantity
+    }
+}
+
+/// Split an order ID into (side, price, sequence number).
+pub fn decode_order_id(order_id: u128) -> (Side, u64, u64) {
+    let side = if order_id >> 127 == 0 {
+        Side::Bid
+    } else {
+        Side::Ask
+    };
+    let price = ((order_id >> 64) & ((1u128 << 63) - 1)) as u64;
+    let sequence = (order_id & u64::MAX as u128) as u64;
+    (side, price, sequence)
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+
+use crate::client::TradingClient;
+use crate::config::{MAX_PRICE, price_from_units, price_to_units};
+use crate::error::ClientError;
+use crate::types::{BalanceSource, PlaceLimitOrderParams, RoundingMode};
+
+/// Verdict on one order of a batch checked by `validate_orders`.
+#[derive(Debug, Clone)]
+pub struct OrderCheck {
+    /// Position of the order in the batch.
+    pub index: usize,
+    pub client_order_id: u64,
+    /// Why DeepBook would abort on the order; `None` if it passed every check.
+    pub error: Option<String>,
+}
+
+impl OrderCheck {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl TradingClient {
+    /// Get the (min, max) price a limit order on the pool may carry, in human units.
+    ///
+    /// DeepBook accepts any multiple of the tick size up to `MAX_PRICE`, so the smallest
+    /// valid price is one tick and the largest is the last tick below `MAX_PRICE`.
+    pub async fn get_price_bounds(&self, pool_key: &str) -> Result<(f64, f64)> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let tick_size = self.get_pool_book_params(pool_key).await?.tick_size;
+
+        let (min_price, max_price) = price_bounds(tick_size);
+        Ok((
+            price_from_units(min_price, base, quote),
+            price_from_units(max_price, base, quote),
+        ))
+    }
+
+    /// Snap a human price onto the pool's tick grid.
+    pub async fn round_to_tick(
+        &self,
+        pool_key: &str,
+        price: f64,
+        mode: RoundingMode,
+    ) -> Result<f64> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let tick_size = self.get_pool_book_params(pool_key).await?.tick_size;
+
+        let price = round_units(price_to_units(price, base, quote), tick_size, mode);
+        Ok(price_from_units(price, base, quote))
+    }
+
+    /// Snap a human base quantity onto the pool's lot grid.
+    pub async fn round_to_lot(
+        &self,
+        pool_key: &str,
+        quantity: f64,
+        mode: RoundingMode,
+    ) -> Result<f64> {
+        let pool = self.config.pool(pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let lot_size = self.get_pool_book_params(pool_key).await?.lot_size;
+
+        Ok(base.from_units(round_units(base.to_units(quantity), lot_size, mode)))
+    }
+
+    /// Fail with `ClientError::InsufficientBalance` unless `source` holds at least
+    /// `required` of the coin, given by key, alias or type. Manager balances are the free
+    /// balance only; amounts still settled on a pool are not counted.
+    pub async fn ensure_balance(
+        &self,
+        coin_type: &str,
+        required: f64,
+        source: BalanceSource,
+    ) -> Result<()> {
+        let coin = self.config.coin(coin_type)?;
+        let have = match &source {
+            BalanceSource::Wallet => {
+                let balance = self.ctx.balance(self.sender, &coin.type_).await?;
+                coin.from_units(u64::try_from(balance).unwrap_or(u64::MAX))
+            }
+            BalanceSource::Manager(manager_key) => {
+                self.get_manager_balance(Some(manager_key), coin_type)
+                    .await?
+            }
+        };
+        if coin.to_units(have) < coin.to_units(required) {
+            return Err(ClientError::InsufficientBalance {
+                coin: self
+                    .config
+                    .coin_key(coin_type)
+                    .unwrap_or(coin_type)
+                    .to_string(),
+                have,
+                need: required,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Check every order of a batch on its own before building the PTB: the fee option,
+    /// price bounds and tick, lot and minimum size, and whether each manager holds
+    /// enough DEEP for the taker fees of its orders so far in the batch. Returns one
+    /// verdict per order, in batch order; the error is only for checks that could not
+    /// be run.
+    pub async fn validate_orders(
+        &self,
+        orders: &[PlaceLimitOrderParams],
+    ) -> Result<Vec<OrderCheck>> {
+        let mut deep_left: HashMap<String, f64> = HashMap::new();
+        let mut checks = Vec::with_capacity(orders.len());
+        for (index, order) in orders.iter().enumerate() {
+            let checked = match self
+                .ensure_fee_option(&order.pool_key, order.pay_with_deep)
+                .await
+            {
+                Ok(()) => self.validate_limit_order(order).await,
+                Err(e) => Err(e),
+            };
+            let error = match checked {
+                Err(e) => Some(e.to_string()),
+                Ok(()) => self.check_deep(order, &mut deep_left).await?,
+            };
+            checks.push(OrderCheck {
+                index,
+                client_order_id: order.client_order_id,
+                error,
+            });
+        }
+        Ok(checks)
+    }
+
+    /// Charge the order's worst-case DEEP fee against what its manager has left.
+    async fn check_deep(
+        &self,
+        order: &PlaceLimitOrderParams,
+        deep_left: &mut HashMap<String, f64>,
+    ) -> Result<Option<String>> {
+        if !order.pay_with_deep || self.whitelisted(&order.pool_key).await? {
+            return Ok(None);
+        }
+        let pool = self.config.pool(&order.pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let price = price_from_units(order.price_units(base, quote), base, quote);
+        let quantity = base.from_units(order.quantity_units(base));
+        let (deep_required, _) = self
+            .get_order_deep_required(&order.pool_key, quantity, price)
+            .await?;
+
+        let manager_key = self.manager_key(order.balance_manager_key.as_deref())?;
+        let available = match deep_left.get(manager_key) {
+            Some(available) => *available,
+            None => self.get_manager_balance(Some(manager_key), "DEEP").await?,
+        };
+        if deep_required > available {
+            deep_left.insert(manager_key.to_string(), available);
+            return Ok(Some(format!(
+                "Order needs {} DEEP for fees, but manager {} has only {} left",
+                deep_required, manager_key, available
+            )));
+        }
+        deep_left.insert(manager_key.to_string(), available - deep_required);
+        Ok(None)
+    }
+
+    /// Reject a limit order locally if DeepBook would abort on its price or size.
+    pub async fn validate_limit_order(&self, params: &PlaceLimitOrderParams) -> Result<()> {
+        let pool = self.config.pool(&params.pool_key)?;
+        let base = self.config.coin(&pool.base_coin)?;
+        let quote = self.config.coin(&pool.quote_coin)?;
+        let book_params = self.get_pool_book_params(&params.pool_key).await?;
+        let tick_size = book_params.tick_size;
+        let (min_price, max_price) = price_bounds(tick_size);
+        let price = params.price_units(base, quote);
+
+        if price < min_price || price > max_price {
+            return Err(anyhow!(
+                "Price {} is outside the allowed range [{}, {}] for pool {}",
+                params.price,
+                price_from_units(min_price, base, quote),
+                price_from_units(max_price, base, quote),
+                params.pool_key
+            ));
+        }
+        if price % tick_size != 0 {
+            return Err(anyhow!(
+                "Price {} is not a multiple of the tick size {} for pool {}",
+                params.price,
+                price_from_units(tick_size, base, quote),
+                params.pool_key
+            ));
+        }
+
+        let quantity = params.quantity_units(base);
+        if quantity < book_params.min_size {
+            return Err(anyhow!(
+                "Quantity {} is below the minimum size {} for pool {}",
+                params.quantity,
+                base.from_units(book_params.min_size),
+                params.pool_key
+            ));
+        }
+        if quantity % book_params.lot_size != 0 {
+            return Err(anyhow!(
+                "Quantity {} is not a multiple of the lot size {} for pool {}",
+                params.quantity,
+                base.from_units(book_params.lot_size),
+                params.pool_key
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The first and last tick-aligned prices DeepBook accepts, in on-chain units.
+fn price_bounds(tick_size: u64) -> (u64, u64) {
+    (tick_size, MAX_PRICE - MAX_PRICE % tick_size)
+}
+
+/// Round `value` to a multiple of `step` in the given direction; halves round up.
+/// Rounding up past `u64::MAX` clamps to the largest multiple of `step` instead.
+pub fn round_units(value: u64, step: u64, mode: RoundingMode) -> u64 {
+    let remainder = value % step;
+    let floor = value - remainder;
+    if remainder == 0 {
+        return value;
+    }
+    let ceil = floor.checked_add(step).unwrap_or(floor);
+    match mode {
+        RoundingMode::Floor => floor,
+        RoundingMode::Ceil => ceil,
+        RoundingMode::Nearest if remainder >= step - remainder => ceil,
+        RoundingMode::Nearest => floor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_support::offline_client;
+    use crate::config::DeepBookConfig;
+    use crate::context::BuildContext;
+    use crate::types::{PoolBookParams, Side};
+
+    fn client() -> TradingClient {
+        let config = DeepBookConfig::new("testnet").unwrap();
+        let pool_id = config.pool("SUI_DBUSDC").unwrap().id().unwrap();
+        offline_client(BuildContext::fixed().with_book_params(
+            pool_id,
+            PoolBookParams {
+                tick_size: 1_000,
+                lot_size: 1_000_000,
+                min_size: 10_000_000,
+            },
+        ))
+    }
+
+    fn order(price_units: u64) -> PlaceLimitOrderParams {
+        PlaceLimitOrderParams {
+            pool_key: "SUI_DBUSDC".to_string(),
+            quantity: 1.0,
+            price_units: Some(price_units),
+            side: Side::Bid,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn price_bounds_are_the_first_and_last_tick() {
+        let client = client();
+        let config = &client.config;
+        let (sui, dbusdc) = (config.coin("SUI").unwrap(), config.coin("DBUSDC").unwrap());
+        let last_tick = MAX_PRICE - MAX_PRICE % 1_000;
+        assert_eq!(
+            client.get_price_bounds("SUI_DBUSDC").await.unwrap(),
+            (0.001, price_from_units(last_tick, sui, dbusdc))
+        );
+        assert!(client.validate_limit_order(&order(1_000)).await.is_ok());
+        assert!(client.validate_limit_order(&order(last_tick)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn validate_orders_checks_the_fee_option() {
+        let mut client = client();
+        let pool_id = client.config.pool("SUI_DBUSDC").unwrap().id().unwrap();
+        client.ctx = client.ctx.clone().with_whitelisted(pool_id, true);
+        let orders = [
+            PlaceLimitOrderParams {
+                pay_with_deep: false,
+                ..order(2_000_000)
+            },
+            order(2_000_000),
+        ];
+        let checks = client.validate_orders(&orders).await.unwrap();
+        assert!(checks[0].error.as_ref().unwrap().contains("whitelisted"));
+        assert_eq!(checks[1].error, None);
+    }
+
+    #[tokio::test]
+    async fn orders_outside_the_price_bounds_are_rejected() {
+        let client = client();
+        for price_units in [0, MAX_PRICE - MAX_PRICE % 1_000 + 1_000] {
+            let error = client
+                .validate_limit_order(&order(price_units))
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("outside the allowed range"));
+        }
+        let error = client
+            .validate_limit_order(&order(1_500))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("tick size"));
+    }
+
+    #[test]
+    fn round_units_edge_cases() {
+        assert_eq!(round_units(0, 10, RoundingMode::Ceil), 0);
+        assert_eq!(round_units(30, 10, RoundingMode::Floor), 30);
+        assert_eq!(round_units(30, 10, RoundingMode::Ceil), 30);
+        assert_eq!(round_units(3, 10, RoundingMode::Floor), 0);
+        assert_eq!(round_units(3, 10, RoundingMode::Ceil), 10);
+        assert_eq!(round_units(34, 10, RoundingMode::Nearest), 30);
+        assert_eq!(round_units(35, 10, RoundingMode::Nearest), 40);
+        assert_eq!(round_units(7, 1, RoundingMode::Ceil), 7);
+        assert_eq!(
+            round_units(u64::MAX, u64::MAX, RoundingMode::Floor),
+            u64::MAX
+        );
+        let largest = u64::MAX - u64::MAX % 10;
+        assert_eq!(round_units(u64::MAX, 10, RoundingMode::Ceil), largest);
+        assert_eq!(round_units(u64::MAX, 10, RoundingMode::Nearest), largest);
+        assert_eq!(
+            round_units(u64::MAX - 1, 4, RoundingMode::Nearest),
+            u64::MAX - 3
+        );
+    }
+}
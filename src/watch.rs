@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::stream::{self, Stream};
+
+use crate::client::TradingClient;
+use crate::types::PoolTradeParams;
+
+/// The governance-controlled settings of a pool that affect how it must be traded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub whitelisted: bool,
+    pub trade_params: PoolTradeParams,
+}
+
+/// A change seen by `watch_pool_config`.
+#[derive(Debug, Clone)]
+pub struct PoolConfigChange {
+    pub pool_key: String,
+    pub previous: PoolConfig,
+    pub current: PoolConfig,
+}
+
+impl PoolConfigChange {
+    pub fn whitelist_changed(&self) -> bool {
+        self.previous.whitelisted != self.current.whitelisted
+    }
+
+    pub fn fees_changed(&self) -> bool {
+        self.previous.trade_params != self.current.trade_params
+    }
+}
+
+impl TradingClient {
+    /// Read a pool's whitelist status and trade params in one go.
+    pub async fn get_pool_config(&self, pool_key: &str) -> Result<PoolConfig> {
+        Ok(PoolConfig {
+            whitelisted: self.whitelisted(pool_key).await?,
+            trade_params: self.get_pool_trade_params(pool_key).await?,
+        })
+    }
+
+    /// Poll a pool's config every `interval` and yield each change against the previous
+    /// poll. The first poll only sets the baseline. A failed poll is yielded as an error
+    /// and the watcher keeps going from the last good config.
+    ///
+    /// DeepBook has no per-pool pause; a disabled package version shows up as failing
+    /// polls instead.
+    pub fn watch_pool_config<'a>(
+        &'a self,
+        pool_key: &'a str,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<PoolConfigChange>> + 'a {
+        stream::unfold(None, move |mut last: Option<PoolConfig>| async move {
+            loop {
+                if last.is_some() {
+                    tokio::time::sleep(interval).await;
+                }
+                let current = match self.get_pool_config(pool_key).await {
+                    Ok(current) => current,
+                    Err(e) => {
+                        if last.is_none() {
+                            tokio::time::sleep(interval).await;
+                        }
+                        return Some((Err(e), last));
+                    }
+                };
+                match last.replace(current) {
+                    Some(previous) if previous != current => {
+                        let change = PoolConfigChange {
+                            pool_key: pool_key.to_string(),
+                            previous,
+                            current,
+                        };
+                        return Some((Ok(change), last));
+                    }
+                    _ => continue,
+                }
+            }
+        })
+    }
+}